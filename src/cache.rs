@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::FIXTURES_ENV;
+use crate::Package;
+
+/// The last scan's results, shown on startup until the next scan.
+#[derive(Debug, Serialize, Deserialize)]
+struct ScanCache {
+    /// Unix time the scan finished.
+    scanned_at: u64,
+    packages: Vec<Package>,
+}
+
+/// `~/.cache/brewsweep/packages.json`, or under `$XDG_CACHE_HOME` if set.
+pub fn cache_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("brewsweep").join("packages.json"))
+}
+
+/// The cached packages and when they were scanned. `None` if the cache is
+/// missing or unreadable, or when scanning fixtures.
+pub fn load_cache() -> Option<(Vec<Package>, SystemTime)> {
+    if std::env::var_os(FIXTURES_ENV).is_some() {
+        return None;
+    }
+
+    let contents = fs::read_to_string(cache_path()?).ok()?;
+    let cache: ScanCache = serde_json::from_str(&contents).ok()?;
+    Some((
+        cache.packages,
+        UNIX_EPOCH + Duration::from_secs(cache.scanned_at),
+    ))
+}
+
+/// Replaces the cache with `packages`, scanned at `scanned_at`. Fixture
+/// scans aren't real installs and are not cached.
+pub fn save_cache(packages: &[Package], scanned_at: SystemTime) -> Result<(), String> {
+    if std::env::var_os(FIXTURES_ENV).is_some() {
+        return Ok(());
+    }
+
+    let path = cache_path().ok_or("Could not determine cache directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let cache = ScanCache {
+        scanned_at: scanned_at
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0),
+        packages: packages.to_vec(),
+    };
+    let contents =
+        serde_json::to_string(&cache).map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,307 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use clap::Parser;
+
+use crate::config::AppConfig;
+use crate::scanner::{HomebrewScanner, ScanScope, ScanTimings, UninstallOptions};
+use crate::{Package, TimeSource};
+
+/// Exit code for failures that aren't scan errors, like writing to stdout.
+const EXIT_FAILURE: i32 = 1;
+
+/// A fast, terminal-based Homebrew usage tracker.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Only scan formulae; skip casks entirely
+    #[arg(long, conflicts_with = "casks_only")]
+    pub formulae_only: bool,
+
+    /// Only scan casks; skip formulae entirely
+    #[arg(long)]
+    pub casks_only: bool,
+
+    /// Only read access times, skipping directory walks and package sizes
+    #[arg(long)]
+    pub fast: bool,
+
+    /// Log every brew command with its exit status and duration (to stderr, or
+    /// to commands.log next to the config file when running the TUI)
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Append each package to this file as JSON, one line per package, as the
+    /// scan finds it
+    #[arg(long, value_name = "PATH")]
+    pub scan_log: Option<PathBuf>,
+
+    /// Scan and print packages without starting the TUI
+    #[arg(long)]
+    pub list: bool,
+
+    /// Like --list, but print JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Scan without the TUI and print only packages not accessed in this many days
+    /// (never-accessed ones included), as with --list or --json
+    #[arg(long, value_name = "DAYS")]
+    pub unused: Option<u64>,
+
+    /// Scan without the TUI and print how long each phase took (as JSON with --json)
+    #[arg(long, conflicts_with_all = ["list", "unused"])]
+    pub benchmark: bool,
+
+    /// Uninstall a package without starting the TUI
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["list", "json", "unused", "benchmark"])]
+    pub uninstall: Option<String>,
+
+    /// List what deletes would remove without uninstalling anything, in the
+    /// TUI or with --uninstall
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip the confirmation prompt for --uninstall
+    #[arg(long, short = 'y', requires = "uninstall")]
+    pub yes: bool,
+}
+
+impl Cli {
+    /// Overrides config values with any flags given on the command line.
+    pub fn apply(&self, config: &mut AppConfig) {
+        if self.formulae_only {
+            config.scan_scope = ScanScope::Formulae;
+        } else if self.casks_only {
+            config.scan_scope = ScanScope::Casks;
+        }
+        if self.fast {
+            config.fast_scan = true;
+        }
+    }
+
+    pub fn is_non_interactive(&self) -> bool {
+        self.list
+            || self.json
+            || self.unused.is_some()
+            || self.benchmark
+            || self.uninstall.is_some()
+    }
+}
+
+/// Runs a non-interactive scan and prints the result, returning the exit code.
+///
+/// Exit codes: 0 success, 1 output error, 2 Homebrew not installed,
+/// 3 a brew command failed, 4 brew output couldn't be parsed.
+pub fn run(cli: &Cli, config: &AppConfig) -> i32 {
+    if let Some(ref name) = cli.uninstall {
+        return uninstall(name, cli.yes, cli.dry_run);
+    }
+
+    let scanner = HomebrewScanner::new().with_options(config.scan_options());
+    let mut packages = match scanner.scan_blocking() {
+        Ok(packages) => packages,
+        Err(e) => {
+            eprintln!("brewsweep: {}", e);
+            return e.exit_code();
+        }
+    };
+    let state = scanner.get_state();
+    let timings = state.timings.clone();
+    if let Some(warning) = state.warning {
+        eprintln!("brewsweep: warning: {}", warning);
+    }
+    for (name, reason) in state.package_warnings {
+        eprintln!("brewsweep: warning: {}: {}", name, reason);
+    }
+
+    if let Some(days) = cli.unused {
+        packages.retain(|package| package.idle_longer_than(days));
+    }
+
+    let result = if cli.benchmark {
+        print_benchmark(packages.len(), &timings, cli.json)
+    } else if cli.json {
+        print_json(&packages)
+    } else {
+        print_list(&packages)
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("brewsweep: {}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Uninstalls `name`, streaming brew's output to stdout.
+///
+/// Asks for confirmation on the terminal unless `yes` or `dry_run` is set. Exit codes match
+/// `run`, plus 1 when the package isn't installed, the prompt is declined or
+/// the uninstall fails.
+fn uninstall(name: &str, yes: bool, dry_run: bool) -> i32 {
+    let scanner = HomebrewScanner::new();
+    let package_type = match scanner.find_installed(name) {
+        Ok(Some(package_type)) => package_type,
+        Ok(None) => {
+            eprintln!("brewsweep: '{}' is not installed", name);
+            return EXIT_FAILURE;
+        }
+        Err(e) => {
+            eprintln!("brewsweep: {}", e);
+            return e.exit_code();
+        }
+    };
+
+    let paths = if dry_run {
+        match scanner.find_installed_paths(name, &package_type) {
+            Ok(paths) => paths,
+            Err(e) => {
+                eprintln!("brewsweep: {}", e);
+                return e.exit_code();
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let package = Package {
+        name: name.to_string(),
+        package_type,
+        version: None,
+        last_accessed: None,
+        time_source: TimeSource::default(),
+        last_accessed_path: None,
+        installed_at: None,
+        paths,
+        size_bytes: None,
+        metadata: None,
+        orphans: None,
+        dependents: Vec::new(),
+        outdated: false,
+    };
+    let options = UninstallOptions {
+        dry_run,
+        ..Default::default()
+    };
+
+    if !yes && !dry_run {
+        match confirm(&format!(
+            "Run `brew uninstall --{} {}`? [y/N] ",
+            package.package_type().to_lowercase(),
+            name
+        )) {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!("brewsweep: aborted");
+                return EXIT_FAILURE;
+            }
+            Err(e) => {
+                eprintln!("brewsweep: {}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let handle =
+        thread::spawn(move || scanner.delete_package_with_output(&package, &options, sender));
+    for line in receiver {
+        println!("{}", line);
+    }
+
+    match handle.join() {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            eprintln!("brewsweep: {}", e);
+            EXIT_FAILURE
+        }
+        Err(_) => {
+            eprintln!("brewsweep: uninstall thread panicked");
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn confirm(prompt: &str) -> io::Result<bool> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", prompt)?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn print_json(packages: &[Package]) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, packages)?;
+    writeln!(stdout)
+}
+
+fn print_benchmark(packages: usize, timings: &ScanTimings, json: bool) -> io::Result<()> {
+    let total = timings.total.as_secs_f64();
+    let per_sec = if total > 0.0 {
+        packages as f64 / total
+    } else {
+        0.0
+    };
+    let mut stdout = io::stdout().lock();
+
+    if json {
+        let phases: Vec<serde_json::Value> = timings
+            .phases()
+            .iter()
+            .map(|(label, time)| {
+                serde_json::json!({ "phase": label, "ms": time.as_secs_f64() * 1000.0 })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "packages": packages,
+            "total_ms": total * 1000.0,
+            "packages_per_sec": per_sec,
+            "phases": phases,
+        });
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        return writeln!(stdout);
+    }
+
+    writeln!(
+        stdout,
+        "Scanned {} packages in {:.3}s ({:.1} packages/sec)",
+        packages, total, per_sec
+    )?;
+    for (label, time) in timings.phases() {
+        let share = if total > 0.0 {
+            time.as_secs_f64() / total * 100.0
+        } else {
+            0.0
+        };
+        writeln!(
+            stdout,
+            "  {:<16} {:>9.3}s {:>5.1}%",
+            label,
+            time.as_secs_f64(),
+            share
+        )?;
+    }
+    Ok(())
+}
+
+fn print_list(packages: &[Package]) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for package in packages {
+        writeln!(
+            stdout,
+            "{}\t{}\t{}\t{}",
+            package.name(),
+            package.package_type(),
+            package.format_last_accessed(),
+            package.last_accessed_path()
+        )?;
+    }
+    Ok(())
+}
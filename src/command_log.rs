@@ -0,0 +1,72 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_path;
+
+/// When set to a non-empty value, behaves like `--verbose`.
+pub const VERBOSE_ENV: &str = "BREWSWEEP_VERBOSE";
+
+static LOG: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+pub fn verbose_from_env() -> bool {
+    std::env::var_os(VERBOSE_ENV).is_some_and(|value| !value.is_empty())
+}
+
+/// Where the TUI writes the command log, next to the config file.
+pub fn default_log_path() -> Option<PathBuf> {
+    Some(config_path()?.parent()?.join("commands.log"))
+}
+
+/// Logs every subsequent `brew` invocation to stderr.
+pub fn init_stderr() {
+    let _ = LOG.set(Mutex::new(Box::new(io::stderr())));
+}
+
+/// Appends every subsequent `brew` invocation to `path`.
+pub fn init_file(path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let file: File = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let _ = LOG.set(Mutex::new(Box::new(file)));
+    Ok(())
+}
+
+/// Records one finished `brew` command, if logging is enabled.
+pub fn record(args: &[&str], outcome: Result<ExitStatus, &io::Error>, elapsed: Duration) {
+    let Some(log) = LOG.get() else {
+        return;
+    };
+
+    let result = match outcome {
+        Ok(status) => match status.code() {
+            Some(code) => format!("exit {}", code),
+            None => "killed by signal".to_string(),
+        },
+        Err(e) => format!("failed to start: {}", e),
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+
+    if let Ok(mut log) = log.lock() {
+        let _ = writeln!(
+            log,
+            "[{}] brew {} -> {} ({:.2}s)",
+            timestamp,
+            args.join(" "),
+            result,
+            elapsed.as_secs_f64()
+        );
+    }
+}
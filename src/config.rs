@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::{HomebrewScanner, MaintenanceAction, ScanOptions, ScanScope};
+
+/// User preferences loaded from `~/.config/brewsweep/config.toml`.
+///
+/// Every field has a default, so a partial (or missing) file is fine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Replace emoji in titles and labels with plain ASCII tags.
+    pub ascii_mode: bool,
+    /// Names of packages already reviewed and kept.
+    pub reviewed: BTreeSet<String>,
+    /// Packages that select-by-age never marks, however long they've been idle.
+    pub keep: BTreeSet<String>,
+    /// Packages that can't be deleted or marked for deletion until unpinned.
+    pub pinned: BTreeSet<String>,
+    /// Initial table layout; toggled at runtime with `c`.
+    pub table_density: TableDensity,
+    /// Blank lines above and below each row's text in the comfortable layout.
+    pub row_padding_vertical: u16,
+    /// Spaces either side of each cell's text.
+    pub row_padding_horizontal: u16,
+    /// Restrict scans to formulae or casks ("all", "formulae", "casks").
+    pub scan_scope: ScanScope,
+    /// Packages smaller than this are folded into one row when grouping is on.
+    pub small_package_threshold_mb: u64,
+    /// Packages at least this big open with dependents and a size breakdown
+    /// in the details view; `i` shows them for any package.
+    pub details_expand_threshold_mb: u64,
+    /// Extra places to check for cask usage, e.g. `~/Library/Application Support/{name}`.
+    pub cask_data_dirs: Vec<String>,
+    /// Require holding `y`/`Enter` to confirm a delete instead of a single press.
+    pub hold_to_confirm: bool,
+    /// Batches of at least this many packages need a second confirmation; 0 disables.
+    pub large_batch_count: usize,
+    /// Batches totalling at least this size need a second confirmation; 0 disables.
+    pub large_batch_size_mb: u64,
+    /// Skip directory walks and sizes, reading one access time per package.
+    pub fast_scan: bool,
+    /// After a delete, select the package that was below the deleted one
+    /// instead of a row picked by position.
+    pub keep_place_on_delete: bool,
+    /// Seconds between access-time refreshes in watch mode (`w`).
+    pub watch_interval_secs: u64,
+    /// Keep the selected package selected when the list is re-sorted,
+    /// instead of jumping back to the top.
+    pub selection_follows_sort: bool,
+    /// Maintenance command to run after each successful single uninstall.
+    pub post_delete_action: PostDeleteAction,
+    /// Brewfile that the maintenance menu's restore action installs from.
+    pub brewfile: String,
+    /// Packages to highlight in the table. Purely visual; they can still be deleted.
+    pub favorites: Vec<String>,
+    /// Color theme, 0-3 for Blue, Emerald, Indigo and Red; saved when it changes.
+    pub color_index: usize,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            ascii_mode: false,
+            reviewed: BTreeSet::new(),
+            keep: BTreeSet::new(),
+            pinned: BTreeSet::new(),
+            table_density: TableDensity::default(),
+            row_padding_vertical: 1,
+            row_padding_horizontal: 1,
+            scan_scope: ScanScope::default(),
+            small_package_threshold_mb: 10,
+            details_expand_threshold_mb: 100,
+            cask_data_dirs: Vec::new(),
+            hold_to_confirm: false,
+            large_batch_count: 10,
+            large_batch_size_mb: 2048,
+            fast_scan: false,
+            keep_place_on_delete: false,
+            watch_interval_secs: 30,
+            selection_follows_sort: false,
+            post_delete_action: PostDeleteAction::default(),
+            brewfile: "~/Brewfile".to_string(),
+            favorites: Vec::new(),
+            color_index: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableDensity {
+    /// Padded multi-line rows.
+    #[default]
+    Comfortable,
+    /// Single-line rows, many more packages per screen.
+    Compact,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostDeleteAction {
+    #[default]
+    None,
+    /// `brew autoremove`, dropping dependencies nothing needs anymore.
+    Autoremove,
+    /// `brew cleanup`, removing old versions and stale downloads.
+    Cleanup,
+}
+
+impl PostDeleteAction {
+    pub fn maintenance_action(self) -> Option<MaintenanceAction> {
+        match self {
+            PostDeleteAction::None => None,
+            PostDeleteAction::Autoremove => Some(MaintenanceAction::Autoremove),
+            PostDeleteAction::Cleanup => Some(MaintenanceAction::Cleanup),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            scope: self.scan_scope,
+            cask_data_dirs: self.cask_data_dirs.clone(),
+            fast: self.fast_scan,
+        }
+    }
+
+    /// The configured Brewfile with `~/` expanded.
+    pub fn brewfile_path(&self) -> PathBuf {
+        PathBuf::from(HomebrewScanner::expand_home(&self.brewfile))
+    }
+
+    /// Writes the config back to disk, creating the directory if needed.
+    pub fn save_config(&self) -> Result<(), String> {
+        let path = config_path().ok_or("Could not determine config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| format!("Failed to encode config: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("brewsweep").join("config.toml"))
+}
+
+/// Loads the config file, falling back to defaults if it is missing or malformed.
+pub fn load_config() -> AppConfig {
+    config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::{format_timestamp, Package, PackageType};
+
+/// File formats the table can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Same shape as `--json`.
+    Json,
+    /// One row per package with the table's columns.
+    Csv,
+    /// `brew "name"` / `cask "name"` lines for `brew bundle`.
+    Brewfile,
+}
+
+impl ExportFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "export.json",
+            ExportFormat::Csv => "export.csv",
+            ExportFormat::Brewfile => "Brewfile",
+        }
+    }
+}
+
+/// Where an export of `format` is written: `~/.brewsweep/`.
+pub fn export_path(format: ExportFormat) -> Option<PathBuf> {
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    Some(home.join(".brewsweep").join(format.file_name()))
+}
+
+/// Writes `packages` in `format`, replacing any earlier export of that
+/// format, and returns the file written.
+pub fn export(format: ExportFormat, packages: &[Package]) -> Result<PathBuf, String> {
+    let path = export_path(format).ok_or("Could not determine home directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    match format {
+        ExportFormat::Json => export_json(packages, &path)?,
+        ExportFormat::Csv => export_csv(packages, &path)?,
+        ExportFormat::Brewfile => export_brewfile(packages, &path)?,
+    }
+    Ok(path)
+}
+
+fn export_json(packages: &[Package], path: &Path) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(packages)
+        .map_err(|e| format!("Failed to serialize packages: {}", e))?;
+    write_file(path, &contents)
+}
+
+/// The table's columns, with machine-friendly values: a local timestamp
+/// instead of a relative time and sizes in bytes. Unknown values are empty.
+pub fn export_csv(items: &[Package], path: &Path) -> Result<(), String> {
+    let mut contents = String::from(
+        "Package Name,Type,Version,Last Accessed,Last Accessed Path,Size (bytes),Orphans\n",
+    );
+    for package in items {
+        let last_accessed = package
+            .last_accessed
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| format_timestamp(since.as_secs()))
+            .unwrap_or_default();
+        let fields = [
+            package.name.clone(),
+            package.package_type().to_string(),
+            package.version.clone().unwrap_or_default(),
+            last_accessed,
+            package.last_accessed_path().to_string(),
+            package
+                .size_bytes
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            package
+                .orphans
+                .map(|count| count.to_string())
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        contents.push_str(&row.join(","));
+        contents.push('\n');
+    }
+    write_file(path, &contents)
+}
+
+/// One `brew` or `cask` entry per package, formulae first, so
+/// `brew bundle --file <path>` reinstalls them on another machine.
+pub fn export_brewfile(items: &[Package], path: &Path) -> Result<(), String> {
+    let mut entries: Vec<(&str, &str)> = items
+        .iter()
+        .map(|package| {
+            let kind = match package.package_type {
+                PackageType::Formula => "brew",
+                PackageType::Cask => "cask",
+            };
+            (kind, package.name.as_str())
+        })
+        .collect();
+    // "brew" sorts before "cask", matching `brew bundle dump`
+    entries.sort();
+
+    let contents: String = entries
+        .iter()
+        .map(|(kind, name)| format!("{} \"{}\"\n", kind, name))
+        .collect();
+    write_file(path, &contents)
+}
+
+/// Quotes a field that contains a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_path;
+use crate::scanner::FIXTURES_ENV;
+use crate::Package;
+
+/// Older scans are dropped once the history grows past this.
+const MAX_ENTRIES: usize = 365;
+
+/// Homebrew footprint at the end of one scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix time the scan finished.
+    pub time: u64,
+    pub packages: usize,
+    /// Sum of package sizes; `None` for `--fast` scans, which skip sizes.
+    #[serde(default)]
+    pub total_bytes: Option<u64>,
+}
+
+/// Where scan totals are kept, next to the config file.
+pub fn history_path() -> Option<PathBuf> {
+    Some(config_path()?.parent()?.join("history.json"))
+}
+
+/// Recorded scans, oldest first. Empty if the file is missing or unreadable.
+pub fn load() -> Vec<HistoryEntry> {
+    history_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Appends the totals of a finished scan. Fixture scans aren't real installs
+/// and are not recorded.
+pub fn record(packages: &[Package]) -> Result<(), String> {
+    if std::env::var_os(FIXTURES_ENV).is_some() {
+        return Ok(());
+    }
+
+    let path = history_path().ok_or("Could not determine config directory")?;
+    let mut history = load();
+    history.push(HistoryEntry {
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0),
+        packages: packages.len(),
+        total_bytes: packages.iter().map(|p| p.size_bytes).sum(),
+    });
+    if history.len() > MAX_ENTRIES {
+        history.drain(..history.len() - MAX_ENTRIES);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let contents = serde_json::to_string_pretty(&history)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
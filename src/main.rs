@@ -1,25 +1,45 @@
+mod cache;
+mod cli;
+mod command_log;
+mod config;
+mod export;
+mod history;
+mod scan_log;
 mod scanner;
+use clap::Parser;
 use color_eyre::eyre::Result;
 use ratatui::{
     crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     layout::{Alignment, Constraint, Layout, Margin, Rect},
     style::{self, Color, Modifier, Style, Stylize},
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{
-        Block, BorderType, Borders, Cell, Gauge, HighlightSpacing, Paragraph, Row, Scrollbar,
-        ScrollbarOrientation, ScrollbarState, Table, TableState,
+        Block, BorderType, Borders, Cell, Clear, Gauge, HighlightSpacing, Paragraph, Row,
+        Scrollbar, ScrollbarOrientation, ScrollbarState, Sparkline, Table, TableState, Wrap,
     },
     DefaultTerminal, Frame,
 };
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
     sync::mpsc,
     thread,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use style::palette::tailwind;
 use unicode_width::UnicodeWidthStr;
 
-use self::scanner::{HomebrewScanner, ScanningState};
+use self::cli::Cli;
+use self::config::{AppConfig, TableDensity};
+use self::export::ExportFormat;
+use self::history::HistoryEntry;
+use self::scanner::{
+    BrokenLink, CancelHandle, HomebrewScanner, MaintenanceAction, MaintenancePreview, ScanScope,
+    ScanningState, UninstallOptions,
+};
 
 const PALETTES: [tailwind::Palette; 4] = [
     tailwind::BLUE,
@@ -27,19 +47,187 @@ const PALETTES: [tailwind::Palette; 4] = [
     tailwind::INDIGO,
     tailwind::RED,
 ];
-const INFO_TEXT: [&str; 3] = [
-    "(Esc) quit | (↑) move up | (↓) move down | (←) move left | (→) move right",
-    "(Shift + →) next color | (Shift + ←) previous color | (Space) Start Scan",
-    "(Enter) Select Package | (d) Delete Selected | (r) Refresh",
+const PALETTE_NAMES: [&str; 4] = ["Blue", "Emerald", "Indigo", "Red"];
+/// How long the palette name stays in the status line after switching.
+const PALETTE_NOTICE: Duration = Duration::from_secs(2);
+const INFO_TEXT: [&str; 4] = [
+    "(Esc) quit | (?) Help | (↑/↓/←/→) move | (c) Compact | (b) Short Paths | (Y) Copy Names | (e/E/B) Export | (f) Idle Filter | (N) Dry Run",
+    "(Shift + →) next color | (Shift + ←) previous color | (1-4) pick color | (z) Group Small | (Space) Start Scan | (/) Search | (o) Orphans",
+    "(Enter) Select Package | (d) Delete Selected | (r) Refresh | (R) Refresh One | (w) Watch | (I) Days Idle | (M) Maintenance | (s/S) Sort",
+    "(m) Mark | (a) Mark by Age | (D) Delete Marked | (A) Re-mark Failed | (x) Reviewed | (K) Keep | (X) Hide Reviewed | (U) Unused Only",
+];
+
+/// Every key binding by screen, shown by `?`.
+const HELP_SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Table",
+        &[
+            ("Space / r", "Scan all packages"),
+            ("↑ ↓ ← →", "Move between rows and columns"),
+            ("g / G", "First row, last row"),
+            ("PgUp / PgDn", "Move a screenful"),
+            ("Enter", "Package details"),
+            ("d", "Delete the selected package"),
+            ("R", "Refresh the selected package"),
+            ("m / D", "Mark, delete marked"),
+            ("a", "Mark packages idle N+ days"),
+            ("A", "Re-mark the last batch's failures"),
+            ("F", "Retry a failed delete with --force"),
+            ("x / X", "Reviewed, hide reviewed"),
+            ("K", "Keep (age marking skips it)"),
+            ("p", "Pin: never delete"),
+            ("/", "Search by name"),
+            ("f", "Idle filter: 30d, 180d, never"),
+            ("U", "Only unused since install"),
+            ("O", "Only outdated"),
+            ("Z", "Filter by size range"),
+            ("s / S", "Cycle sort, reverse sort"),
+            ("I", "Days Idle column"),
+            ("c / b", "Compact rows, short paths"),
+            ("z", "Group small packages"),
+            ("w", "Watch access times"),
+            ("o", "Orphaned dependencies"),
+            ("N", "Dry run for deletes"),
+            ("Y", "Copy visible names"),
+            ("e / E / B", "Export JSON, CSV, Brewfile"),
+            ("T", "Footprint history"),
+            ("t", "Top space consumers"),
+            ("M", "Maintenance menu"),
+            ("Shift ← →", "Previous, next color"),
+            ("1-4", "Pick a color"),
+            ("Esc", "Clear search, or quit"),
+        ],
+    ),
+    (
+        "Scanning",
+        &[
+            ("Space", "Pause, resume or retry"),
+            ("Enter", "Results, once complete"),
+            ("w", "Scan warnings, if any"),
+            ("t", "Top space consumers"),
+        ],
+    ),
+    (
+        "Details",
+        &[
+            ("Enter/Space", "Back to the table"),
+            ("d", "Delete this package"),
+            ("↑ ↓", "Scroll dependencies"),
+            ("v / r", "Verify files, reveal path"),
+            ("o", "Open the homepage"),
+            ("u", "Upgrade with brew upgrade"),
+            ("p", "Pin or unpin"),
+            ("R", "Refresh this package"),
+            ("t / i", "Show dates, full detail"),
+        ],
+    ),
+    (
+        "Confirm delete",
+        &[
+            ("y / Enter", "Delete"),
+            ("n / Space", "Cancel"),
+            ("z", "Casks: toggle --zap"),
+            ("f", "Batches: toggle --force"),
+        ],
+    ),
+    (
+        "Uninstalling",
+        &[
+            ("Space", "Cancel the uninstall"),
+            ("c", "Continue in the background"),
+        ],
+    ),
+    (
+        "Maintenance",
+        &[
+            ("c / a", "Cleanup, autoremove"),
+            ("p", "Prune the download cache"),
+            ("b", "Restore from the Brewfile"),
+            ("l", "Broken links in bin"),
+            ("y / n", "Run or cancel a dry run"),
+        ],
+    ),
+    (
+        "Anywhere",
+        &[
+            ("?", "This help; any key closes it"),
+            ("Esc", "Back to the previous screen"),
+            ("q", "Quit; asks first if busy"),
+        ],
+    ),
 ];
 
-const ITEM_HEIGHT: usize = 4;
+/// Width of each column in the help overlay.
+const HELP_COLUMN_WIDTH: u16 = 48;
+
+/// Height of the key-hint footer under the table.
+const FOOTER_HEIGHT: u16 = 8;
+
+/// How long the confirm key must be held when `hold_to_confirm` is on.
+const HOLD_TO_CONFIRM: Duration = Duration::from_millis(1500);
+
+/// Longest gap between key-repeat events that still counts as holding.
+/// Terminals wait a few hundred milliseconds before repeating a key.
+const HOLD_RELEASE_GAP: Duration = Duration::from_millis(600);
+
+/// Packages listed on the top space consumers screen.
+const TOP_SIZES: usize = 20;
+
+/// Dependencies listed at once in the details view; longer lists scroll.
+const DEPS_ROWS: usize = 5;
+
+/// Upper bound for `row_padding_vertical` and `row_padding_horizontal`, so one
+/// row or cell can't fill the screen.
+const MAX_ROW_PADDING: u16 = 5;
+
+/// Where the optional "Days Idle" column goes, right after "Last Accessed".
+const IDLE_COLUMN: usize = 4;
+
+/// A package name with the on-disk size of each of its paths.
+type SizeBreakdown = (String, Vec<(String, u64)>);
+
+/// A package name with its direct dependencies, or why they couldn't be listed.
+type PackageDeps = (String, Result<Vec<String>, String>);
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let cli = Cli::parse();
+    let mut config = config::load_config();
+    cli.apply(&mut config);
+
+    if cli.verbose || command_log::verbose_from_env() {
+        if cli.is_non_interactive() {
+            command_log::init_stderr();
+        } else if let Some(path) = command_log::default_log_path() {
+            if let Err(e) = command_log::init_file(&path) {
+                eprintln!("brewsweep: {}", e);
+            }
+        }
+    }
+
+    if let Some(ref path) = cli.scan_log {
+        if let Err(e) = scan_log::init(path) {
+            eprintln!("brewsweep: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if cli.is_non_interactive() {
+        let code = cli::run(&cli, &config);
+        scan_log::close();
+        std::process::exit(code);
+    }
+
     let terminal = ratatui::init();
-    let app_result = App::new().run(terminal);
+    let mut app = App::new(config);
+    app.dry_run = cli.dry_run;
+    app.load_cached_results();
+    if !HomebrewScanner::brew_installed() {
+        app.app_state = AppState::NoBrew;
+    }
+    let app_result = app.run(terminal);
     ratatui::restore();
+    scan_log::close();
     app_result
 }
 
@@ -54,6 +242,7 @@ struct TableColors {
     normal_row_color: Color,
     alt_row_color: Color,
     footer_border_color: Color,
+    favorite_fg: Color,
 }
 
 impl TableColors {
@@ -69,24 +258,101 @@ impl TableColors {
             normal_row_color: tailwind::SLATE.c950,
             alt_row_color: tailwind::SLATE.c900,
             footer_border_color: color.c400,
+            favorite_fg: tailwind::AMBER.c300,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Package {
     name: String,
+    #[serde(rename = "type")]
     package_type: PackageType,
+    /// Highest installed version; `None` when no version directory was found.
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default, with = "unix_timestamp")]
     last_accessed: Option<SystemTime>,
+    /// Whether `last_accessed` is a real access time or a modification time.
+    #[serde(default)]
+    time_source: TimeSource,
+    #[serde(default, rename = "path")]
     last_accessed_path: Option<String>,
+    /// When the installed version was poured, from its `INSTALL_RECEIPT.json`
+    /// or else `brew info`.
+    #[serde(default, with = "unix_timestamp")]
+    installed_at: Option<SystemTime>,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    size_bytes: Option<u64>,
+    /// Enrichment from `brew info --json=v2`; `None` when it couldn't be read.
+    #[serde(default)]
+    metadata: Option<PackageMetadata>,
+    /// Installed packages left without dependents if this one were removed.
+    #[serde(default)]
+    orphans: Option<usize>,
+    /// Installed packages that depend on this one directly.
+    #[serde(default)]
+    dependents: Vec<String>,
+    /// A newer version is available, per `brew outdated`.
+    #[serde(default)]
+    outdated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PackageMetadata {
+    #[serde(default)]
+    license: Option<String>,
+    /// Project homepage URL.
+    #[serde(default)]
+    homepage: Option<String>,
+    /// Build options the installed version was built with, e.g. `--HEAD`.
+    #[serde(default)]
+    used_options: Vec<String>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum PackageType {
     Formula,
     Cask,
 }
 
+/// Which file timestamp a package's last-used time was read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TimeSource {
+    /// Access time, updated when the package is actually used.
+    #[default]
+    Atime,
+    /// Modification time, used when access times aren't available or are
+    /// older than the last change (e.g. on `noatime` volumes).
+    Mtime,
+}
+
+/// (De)serializes an optional `SystemTime` as whole seconds since the UNIX epoch.
+mod unix_timestamp {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        let secs = Option::<u64>::deserialize(deserializer)?;
+        Ok(secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+}
+
 impl Package {
     fn get_display_fields(&self) -> Vec<String> {
         vec![
@@ -95,59 +361,60 @@ impl Package {
                 PackageType::Formula => "Formula".to_string(),
                 PackageType::Cask => "Cask".to_string(),
             },
+            self.version.clone().unwrap_or_else(|| "-".to_string()),
             self.format_last_accessed(),
             self.last_accessed_path
                 .as_deref()
                 .unwrap_or("no path")
                 .to_string(),
+            format!(
+                "{:>9}",
+                self.size_bytes.map_or_else(|| "-".to_string(), format_size)
+            ),
+            self.orphans
+                .map_or_else(|| "-".to_string(), |count| count.to_string()),
         ]
     }
 
     fn format_last_accessed(&self) -> String {
         match self.last_accessed {
-            Some(time) => {
-                match time.elapsed() {
-                    Ok(duration) => {
-                        let secs = duration.as_secs();
-
-                        if secs < 60 {
-                            "Just now".to_string()
-                        } else if secs < 3600 {
-                            let mins = secs / 60;
-                            format!("{} min{} ago", mins, if mins == 1 { "" } else { "s" })
-                        } else if secs < 86400 {
-                            let hours = secs / 3600;
-                            format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
-                        } else if secs < 2592000 {
-                            // 30 days
-                            let days = secs / 86400;
-                            format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
-                        } else if secs < 31536000 {
-                            // 365 days
-                            let months = secs / 2592000;
-                            format!("{} month{} ago", months, if months == 1 { "" } else { "s" })
-                        } else {
-                            let years = secs / 31536000;
-                            format!("{} year{} ago", years, if years == 1 { "" } else { "s" })
-                        }
-                    }
-                    Err(_) => {
-                        // If we can't calculate elapsed time, show the actual date
-                        use std::time::UNIX_EPOCH;
-                        if let Ok(duration_since_epoch) = time.duration_since(UNIX_EPOCH) {
-                            let timestamp = duration_since_epoch.as_secs();
-                            // Simple date formatting (you might want to use chrono crate for better formatting)
-                            format!("Timestamp: {}", timestamp)
-                        } else {
-                            "Unknown date".to_string()
-                        }
-                    }
-                }
-            }
+            Some(time) => format_elapsed(time),
             None => "Never accessed".to_string(),
         }
     }
 
+    /// Not accessed since it was installed. False when either time is unknown.
+    fn unused_since_install(&self) -> bool {
+        match (self.last_accessed, self.installed_at) {
+            (Some(accessed), Some(installed)) => accessed <= installed,
+            _ => false,
+        }
+    }
+
+    fn license(&self) -> &str {
+        match self.metadata {
+            Some(ref metadata) => metadata.license.as_deref().unwrap_or("Not specified"),
+            None => "Metadata unavailable",
+        }
+    }
+
+    fn homepage(&self) -> Option<&str> {
+        self.metadata.as_ref()?.homepage.as_deref()
+    }
+
+    /// Non-default install options; empty for casks and default builds.
+    fn used_options(&self) -> &[String] {
+        self.metadata
+            .as_ref()
+            .map_or(&[], |metadata| metadata.used_options.as_slice())
+    }
+
+    fn format_size(&self) -> String {
+        self.size_bytes
+            .map(format_size)
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -163,11 +430,217 @@ impl Package {
         self.last_accessed_path.as_deref().unwrap_or("")
     }
 
-    fn last_accessed(&self) -> String {
-        self.last_accessed
-            .map(|time| format!("{:?}", time))
-            .unwrap_or_else(|| "Unknown".to_string())
+    /// Whole days since the last access; `None` when never accessed.
+    fn days_idle(&self) -> Option<u64> {
+        let idle = self.last_accessed?.elapsed().ok()?;
+        Some(idle.as_secs() / 86400)
+    }
+
+    /// Not accessed within `days`, including never-accessed packages.
+    fn idle_longer_than(&self, days: u64) -> bool {
+        match self.last_accessed {
+            Some(time) => time
+                .elapsed()
+                .is_ok_and(|age| age > Duration::from_secs(days * 86400)),
+            None => true,
+        }
+    }
+
+    fn set_last_accessed(&mut self, accessed: Option<(SystemTime, TimeSource)>) {
+        self.last_accessed = accessed.map(|(time, _)| time);
+        self.time_source = accessed.map(|(_, source)| source).unwrap_or_default();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortMode {
+    Usage,
+    Name,
+    Type,
+    Size,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Usage => SortMode::Name,
+            SortMode::Name => SortMode::Type,
+            SortMode::Type => SortMode::Size,
+            SortMode::Size => SortMode::Usage,
+        }
+    }
+
+    fn label(self, reversed: bool) -> &'static str {
+        match (self, reversed) {
+            (SortMode::Usage, false) => "Usage (least recently used first)",
+            (SortMode::Usage, true) => "Usage (most recently used first)",
+            (SortMode::Name, false) => "Name (A-Z)",
+            (SortMode::Name, true) => "Name (Z-A)",
+            (SortMode::Type, false) => "Type (formulae first)",
+            (SortMode::Type, true) => "Type (casks first)",
+            (SortMode::Size, false) => "Size (largest first)",
+            (SortMode::Size, true) => "Size (smallest first)",
+        }
+    }
+
+    /// Table column the mode sorts on, before the optional "Days Idle" column.
+    fn column(self) -> usize {
+        match self {
+            SortMode::Name => 0,
+            SortMode::Type => 1,
+            SortMode::Usage => 3,
+            SortMode::Size => 5,
+        }
+    }
+
+    /// Whether the unreversed order runs from small to large values.
+    fn ascending(self) -> bool {
+        self != SortMode::Size
+    }
+
+    /// The mode's unreversed order. Never-accessed packages count as the
+    /// oldest and unknown sizes as zero.
+    fn compare(self, a: &Package, b: &Package) -> std::cmp::Ordering {
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+        match self {
+            SortMode::Usage => a.last_accessed.cmp(&b.last_accessed),
+            SortMode::Name => by_name(),
+            SortMode::Type => {
+                let is_cask = |p: &Package| p.package_type == PackageType::Cask;
+                is_cask(a).cmp(&is_cask(b)).then_with(by_name)
+            }
+            SortMode::Size => b.size_bytes.unwrap_or(0).cmp(&a.size_bytes.unwrap_or(0)),
+        }
+    }
+}
+
+/// Quick filter on time since last access, cycled with `f`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IdleFilter {
+    All,
+    Unused30,
+    Unused180,
+    Never,
+}
+
+impl IdleFilter {
+    fn next(self) -> Self {
+        match self {
+            IdleFilter::All => IdleFilter::Unused30,
+            IdleFilter::Unused30 => IdleFilter::Unused180,
+            IdleFilter::Unused180 => IdleFilter::Never,
+            IdleFilter::Never => IdleFilter::All,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IdleFilter::All => "All packages",
+            IdleFilter::Unused30 => "Unused 30+ days",
+            IdleFilter::Unused180 => "Unused 180+ days",
+            IdleFilter::Never => "Never accessed",
+        }
     }
+
+    /// Never-accessed packages count as idle for any number of days.
+    fn matches(self, package: &Package) -> bool {
+        match self {
+            IdleFilter::All => true,
+            IdleFilter::Unused30 => package.idle_longer_than(30),
+            IdleFilter::Unused180 => package.idle_longer_than(180),
+            IdleFilter::Never => package.last_accessed.is_none(),
+        }
+    }
+}
+
+/// Inclusive bounds on a package's size, parsed from input like `>100MB`,
+/// `<1GB` or `10MB-1GB`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SizeRange {
+    min: Option<u64>,
+    max: Option<u64>,
+}
+
+impl SizeRange {
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if let Some(rest) = input.strip_prefix(">=").or_else(|| input.strip_prefix('>')) {
+            return Ok(SizeRange {
+                min: Some(parse_size(rest)?),
+                max: None,
+            });
+        }
+        if let Some(rest) = input.strip_prefix("<=").or_else(|| input.strip_prefix('<')) {
+            return Ok(SizeRange {
+                min: None,
+                max: Some(parse_size(rest)?),
+            });
+        }
+        let Some((low, high)) = input.split_once('-') else {
+            return Err("Use >SIZE, <SIZE or SIZE-SIZE, e.g. >100MB or 10MB-1GB".to_string());
+        };
+
+        let (min, max) = (parse_size(low)?, parse_size(high)?);
+        if min > max {
+            return Err(format!("{} is larger than {}", low.trim(), high.trim()));
+        }
+        Ok(SizeRange {
+            min: Some(min),
+            max: Some(max),
+        })
+    }
+
+    fn contains(self, size: u64) -> bool {
+        self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max)
+    }
+
+    fn label(self) -> String {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{} - {}", format_size(min), format_size(max)),
+            (Some(min), None) => format!(">{}", format_size(min)),
+            (None, Some(max)) => format!("<{}", format_size(max)),
+            (None, None) => "any".to_string(),
+        }
+    }
+}
+
+/// Progress of one package within a batch uninstall.
+#[derive(Debug, Clone, PartialEq)]
+enum BatchStatus {
+    Pending,
+    InProgress,
+    Done,
+    Failed(String),
+}
+
+impl BatchStatus {
+    fn glyph(&self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (BatchStatus::Pending, false) => "○",
+            (BatchStatus::InProgress, false) => "◐",
+            (BatchStatus::Done, false) => "✔",
+            (BatchStatus::Failed(_), false) => "✘",
+            (BatchStatus::Pending, true) => "[ ]",
+            (BatchStatus::InProgress, true) => "[~]",
+            (BatchStatus::Done, true) => "[+]",
+            (BatchStatus::Failed(_), true) => "[x]",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            BatchStatus::Pending => Color::Gray,
+            BatchStatus::InProgress => Color::Yellow,
+            BatchStatus::Done => Color::Green,
+            BatchStatus::Failed(_) => Color::Red,
+        }
+    }
+}
+
+/// Progress messages sent by the batch uninstall thread.
+enum BatchEvent {
+    Started(usize),
+    Finished(usize, Result<(), String>),
 }
 
 #[derive(Debug, Clone)]
@@ -178,50 +651,275 @@ enum AppState {
     PackageSelected(usize),
     ConfirmDelete(usize),
     Deleting(usize),
+    ConfirmBatchDelete,
+    BatchDeleting,
+    /// Prompt for a number of days; holds the digits typed so far.
+    SelectByAge(String),
+    /// Prompt for a size range; holds the text typed so far.
+    SizeFilter(String),
+    MaintenanceMenu,
+    /// Dangling symlinks in the Homebrew `bin` directory.
+    BrokenLinks,
+    ConfirmMaintenance(MaintenanceAction),
+    RunningMaintenance(MaintenanceAction),
+    /// Package count and size recorded at each scan.
+    History,
+    /// Packages the last scan could only partly read.
+    ScanWarnings,
+    /// No `brew` on `PATH`: how to install Homebrew instead of the table.
+    NoBrew,
+    /// The largest packages; holds the highlighted row.
+    TopSizes(usize),
+    /// Quit pressed while something is running; holds the screen it
+    /// interrupted, drawn underneath.
+    ConfirmQuit(Box<AppState>),
 }
 
 struct App {
     state: TableState,
     items: Vec<Package>,
-    longest_item_lens: (u16, u16, u16, u16),
+    filtered_indices: Vec<usize>,
+    hide_reviewed: bool,
+    /// Only list packages never used since they were installed.
+    only_unused: bool,
+    /// Only list packages with a newer version available.
+    only_outdated: bool,
+    /// Show paths relative to the Homebrew prefix instead of in full.
+    short_paths: bool,
+    group_small: bool,
+    small_group_expanded: bool,
+    /// Count and total size of packages folded into the summary row.
+    small_group: Option<(usize, u64)>,
+    longest_item_lens: (u16, u16, u16, u16, u16),
     scroll_state: ScrollbarState,
+    /// Last known terminal size (columns, rows), updated on resize.
+    terminal_size: (u16, u16),
     colors: TableColors,
     color_index: usize,
+    /// When the palette was last switched, to name it briefly in the status line.
+    palette_switched: Option<Instant>,
     app_state: AppState,
     scanner: Option<HomebrewScanner>,
     scan_handle: Option<thread::JoinHandle<()>>,
     delete_output_receiver: Option<mpsc::Receiver<String>>,
     delete_result_receiver: Option<mpsc::Receiver<Result<(), String>>>,
+    /// Kills the single-package uninstall in progress.
+    delete_cancel: CancelHandle,
+    /// Package still uninstalling after `c` left its output view.
+    background_delete: Option<String>,
+    /// Kills the running maintenance command on `Space`.
+    maintenance_cancel: CancelHandle,
+    /// Result of the uninstall that chained the running `post_delete_action`.
+    chained_delete_message: Option<String>,
+    /// Everything the running or last brew command printed.
     delete_output: Vec<String>,
-    delete_message: Option<String>,
-    delete_success: bool,
+    /// Lines the command output is scrolled up from its tail; 0 follows new output.
+    output_scroll_back: usize,
+    status_message: Option<String>,
+    status_success: bool,
+    /// Package names from config rendered with an accent color and a star.
+    favorites: HashSet<String>,
+    /// When the confirm key was first pressed and last repeated, while holding.
+    confirm_hold: Option<(Instant, Instant)>,
+    /// Show the numeric "Days Idle" column, toggled with `I`.
+    show_idle_days: bool,
+    /// Show dates next to relative times in the details view, toggled with `t`.
+    show_dates: bool,
+    /// A large batch has had its first `y`; the next one deletes.
+    large_batch_armed: bool,
+    /// Run the next batch uninstall with `--force`, toggled with `f` on its confirm screen.
+    batch_force: bool,
+    /// Uninstall the cask awaiting confirmation with `--zap`, toggled with `z`.
+    delete_zap: bool,
+    /// Deletes only list what they would remove, toggled with `N` or `--dry-run`.
+    dry_run: bool,
+    /// The `?` key list is drawn over the current screen until the next key.
+    show_help: bool,
+    /// Page of the help overlay on screen.
+    help_page: usize,
+    /// Screens to return to with Esc, most recent last.
+    back_stack: Vec<AppState>,
+    /// Package whose last uninstall failed, retryable with `F`.
+    retry_target: Option<String>,
+    /// When watch mode last refreshed access times; `None` when not watching.
+    watching_since: Option<Instant>,
+    watch_receiver: Option<mpsc::Receiver<Vec<Package>>>,
+    /// A single package being re-scanned with `R`.
+    refresh_receiver: Option<mpsc::Receiver<Result<Package, String>>>,
+    /// Only show packages whose size falls in this range.
+    size_filter: Option<SizeRange>,
+    /// Only show packages idle this long, cycled with `f`.
+    idle_filter: IdleFilter,
+    /// Why the size range typed into the prompt was rejected.
+    size_filter_error: Option<String>,
+    /// Only show packages whose name contains this, ignoring case.
+    filter: String,
+    /// Keystrokes go to `filter` after `/`, until Enter or Esc.
+    searching: bool,
+    /// Scan totals shown by the history view, loaded when it opens.
+    history: Vec<HistoryEntry>,
+    /// A scan has finished without error, so an empty list means nothing is installed.
+    has_scanned: bool,
+    /// When the packages shown were scanned, kept with them in the scan cache.
+    scanned_at: Option<SystemTime>,
+    /// The packages shown were loaded from the scan cache rather than scanned this run.
+    from_cache: bool,
+    /// Package to re-select once a refresh finishes, with `selection_follows_sort`.
+    rescan_selection: Option<String>,
+    verify_result: Option<(String, Vec<String>)>,
+    /// Outcome of the last "reveal in file manager" or "open homepage",
+    /// keyed by package name; `Ok` holds what was opened.
+    open_result: Option<(String, Result<&'static str, String>)>,
+    /// Indices into `items` marked for a batch uninstall.
+    marked: HashSet<usize>,
+    /// Formulae the last `brew autoremove --dry-run` would remove, marked in the table.
+    autoremovable: HashSet<String>,
+    /// Per-package status of the current (or last) batch, keyed by index into `items`.
+    batch_status: HashMap<usize, BatchStatus>,
+    batch_event_receiver: Option<mpsc::Receiver<BatchEvent>>,
+    /// Size `brew info` reports for the package in the details view, keyed by name.
+    reported_size: Option<(String, Option<u64>)>,
+    reported_size_receiver: Option<mpsc::Receiver<(String, Option<u64>)>>,
+    /// Direct dependencies of the package in the details view, keyed by name.
+    current_deps: Option<PackageDeps>,
+    deps_receiver: Option<mpsc::Receiver<PackageDeps>>,
+    /// First dependency shown when the list is longer than its section.
+    deps_scroll: usize,
+    /// Dangling symlinks in `<prefix>/bin` from the last scan.
+    broken_links: Vec<BrokenLink>,
+    /// Show dependents and the size breakdown for every package, not just large ones.
+    full_details: bool,
+    /// Per-path sizes of the package open in the details view.
+    size_breakdown: Option<SizeBreakdown>,
+    size_breakdown_receiver: Option<mpsc::Receiver<SizeBreakdown>>,
+    /// Installed packages that depend on the package awaiting delete
+    /// confirmation: the scan's list until `brew uses` answers.
+    confirm_dependents: Vec<String>,
+    dependents_receiver: Option<mpsc::Receiver<Result<Vec<String>, String>>>,
+    /// Location and size of the download cache, measured when the menu opens.
+    cache_size: Option<Result<(PathBuf, u64), String>>,
+    cache_size_receiver: Option<mpsc::Receiver<Result<(PathBuf, u64), String>>>,
+    /// Cache size before a prune, while its size afterwards is measured.
+    pruned_from: Option<u64>,
+    /// Dry-run result shown on the maintenance confirm screen.
+    maintenance_preview: Option<Result<MaintenancePreview, String>>,
+    maintenance_preview_receiver: Option<mpsc::Receiver<Result<MaintenancePreview, String>>>,
+    config: AppConfig,
+    sort_mode: SortMode,
+    /// Run `sort_mode` backwards, toggled with `S`.
+    sort_reversed: bool,
+    density: TableDensity,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(config: AppConfig) -> Self {
+        // An out-of-range index from a hand-edited config falls back to the first palette
+        let color_index = Some(config.color_index)
+            .filter(|&index| index < PALETTES.len())
+            .unwrap_or(0);
         Self {
             state: TableState::default().with_selected(0),
-            longest_item_lens: (20, 10, 15, 20),
+            longest_item_lens: (20, 10, 15, 20, 8),
             scroll_state: ScrollbarState::new(0),
-            colors: TableColors::new(&PALETTES[0]),
-            color_index: 0,
+            terminal_size: (0, 0),
+            colors: TableColors::new(&PALETTES[color_index]),
+            color_index,
+            palette_switched: None,
             items: Vec::new(),
+            filtered_indices: Vec::new(),
+            hide_reviewed: false,
+            only_unused: false,
+            only_outdated: false,
+            short_paths: false,
+            group_small: false,
+            small_group_expanded: false,
+            small_group: None,
             app_state: AppState::Table,
             scanner: None,
             scan_handle: None,
             delete_output_receiver: None,
+            background_delete: None,
             delete_result_receiver: None,
+            delete_cancel: CancelHandle::default(),
+            maintenance_cancel: CancelHandle::default(),
+            chained_delete_message: None,
             delete_output: Vec::new(),
-            delete_message: None,
-            delete_success: false,
+            output_scroll_back: 0,
+            status_message: None,
+            status_success: false,
+            favorites: HashSet::new(),
+            confirm_hold: None,
+            show_dates: false,
+            show_idle_days: false,
+            large_batch_armed: false,
+            batch_force: false,
+            delete_zap: false,
+            dry_run: false,
+            show_help: false,
+            help_page: 0,
+            back_stack: Vec::new(),
+            retry_target: None,
+            watching_since: None,
+            watch_receiver: None,
+            refresh_receiver: None,
+            size_filter: None,
+            idle_filter: IdleFilter::All,
+            size_filter_error: None,
+            filter: String::new(),
+            searching: false,
+            history: Vec::new(),
+            rescan_selection: None,
+            has_scanned: false,
+            scanned_at: None,
+            from_cache: false,
+            verify_result: None,
+            open_result: None,
+            marked: HashSet::new(),
+            autoremovable: HashSet::new(),
+            batch_status: HashMap::new(),
+            batch_event_receiver: None,
+            reported_size: None,
+            reported_size_receiver: None,
+            current_deps: None,
+            deps_receiver: None,
+            deps_scroll: 0,
+            broken_links: Vec::new(),
+            full_details: false,
+            size_breakdown: None,
+            size_breakdown_receiver: None,
+            cache_size: None,
+            confirm_dependents: Vec::new(),
+            dependents_receiver: None,
+            cache_size_receiver: None,
+            pruned_from: None,
+            maintenance_preview: None,
+            maintenance_preview_receiver: None,
+            config: AppConfig::default(),
+            sort_mode: SortMode::Usage,
+            sort_reversed: false,
+            density: TableDensity::Comfortable,
         }
+        .with_config(config)
+    }
+
+    fn with_config(mut self, config: AppConfig) -> Self {
+        self.density = config.table_density;
+        self.favorites = config.favorites.iter().cloned().collect();
+        self.config = config;
+        self
     }
 
     fn start_scanning(&mut self) {
+        self.stop_watching();
         self.app_state = AppState::Scanning;
+        self.rescan_selection = self.followed_selection();
         self.items.clear();
+        self.filtered_indices.clear();
+        self.retry_target = None;
+        self.marked.clear();
+        self.batch_status.clear();
 
-        let scanner = HomebrewScanner::new();
+        let scanner = HomebrewScanner::new().with_options(self.config.scan_options());
         let handle = scanner.start_scan();
 
         self.scanner = Some(scanner);
@@ -232,603 +930,4530 @@ impl App {
         if let Some(ref scanner) = self.scanner {
             let scanning_state = scanner.get_state();
 
-            if scanning_state.scan_complete {
+            // A failed scan stays on the scanning screen so it can be retried
+            if scanning_state.scan_complete && scanning_state.error_message.is_none() {
+                let mut warning = scanning_state.warning;
                 self.items = scanner.get_packages();
-                self.sort_packages_by_usage();
+                self.has_scanned = true;
+                self.scanned_at = Some(SystemTime::now());
+                self.from_cache = false;
+                if let Err(e) = history::record(&self.items) {
+                    warning.get_or_insert(format!("Could not save scan history: {}", e));
+                }
+                if let Err(e) = self.save_cache() {
+                    warning.get_or_insert(format!("Could not save scan cache: {}", e));
+                }
+                if let Some(warning) = warning {
+                    self.status_success = false;
+                    self.status_message = Some(warning);
+                }
+                self.broken_links = scanner.get_broken_links();
+                self.sort_packages();
+                if let Some(name) = self.rescan_selection.take() {
+                    self.select_by_name(&name);
+                }
                 self.app_state = AppState::ScanComplete;
                 self.longest_item_lens = constraint_len_calculator(&self.items);
-                self.scroll_state = ScrollbarState::new(if self.items.is_empty() {
-                    0
-                } else {
-                    (self.items.len() - 1) * ITEM_HEIGHT
-                });
-                if !self.items.is_empty() {
-                    self.state.select(Some(0));
-                }
             }
         }
     }
 
-    fn select_package(&mut self) {
-        if let Some(selected_index) = self.state.selected() {
-            if selected_index < self.items.len() {
-                self.app_state = AppState::PackageSelected(selected_index);
-            }
-        }
+    /// Switches to `state`, remembering the current screen for Esc.
+    fn open_screen(&mut self, state: AppState) {
+        let previous = std::mem::replace(&mut self.app_state, state);
+        self.back_stack.push(previous);
     }
 
-    fn confirm_delete(&mut self, package_index: usize) {
-        self.app_state = AppState::ConfirmDelete(package_index);
+    /// Screens where Esc quits: there is nothing further back.
+    fn at_top_level(&self) -> bool {
+        matches!(
+            self.app_state,
+            AppState::Table | AppState::Scanning | AppState::ScanComplete
+        )
     }
 
-    fn delete_selected_package(&mut self) {
-        if let Some(selected_index) = self.state.selected() {
-            if selected_index < self.items.len() {
-                self.confirm_delete(selected_index);
-            }
+    /// Esc below the top level: back to the screen this one was opened from.
+    /// Ignored while an uninstall or maintenance command runs, which has its
+    /// own cancel key.
+    fn go_back(&mut self) {
+        let running = match self.app_state {
+            AppState::Deleting(_) => self.delete_running(),
+            AppState::BatchDeleting => self.batch_running(),
+            AppState::RunningMaintenance(_) => true,
+            _ => false,
+        };
+        if !running {
+            self.app_state = self.back_stack.pop().unwrap_or(AppState::Table);
         }
     }
 
-    fn execute_delete(&mut self, package_index: usize) {
-        if package_index < self.items.len() {
-            self.app_state = AppState::Deleting(package_index);
-            let package = self.items[package_index].clone();
-
-            // Clear previous output
-            self.delete_output.clear();
-
-            // Create channels for output and result
-            let (output_sender, output_receiver) = mpsc::channel();
-            let (result_sender, result_receiver) = mpsc::channel();
+    /// `r` on the no-brew screen: carry on to the table once `brew` is on `PATH`.
+    fn recheck_brew(&mut self) {
+        if HomebrewScanner::brew_installed() {
+            self.app_state = AppState::Table;
+        } else {
+            self.status_message = Some("Still no brew on PATH".to_string());
+            self.status_success = false;
+        }
+    }
 
-            self.delete_output_receiver = Some(output_receiver);
-            self.delete_result_receiver = Some(result_receiver);
+    /// Shows the last scan's results straight away, if they were cached.
+    fn load_cached_results(&mut self) {
+        let Some((items, scanned_at)) = cache::load_cache() else {
+            return;
+        };
+        self.items = items;
+        self.has_scanned = true;
+        self.scanned_at = Some(scanned_at);
+        self.from_cache = true;
+        self.sort_packages();
+        self.longest_item_lens = constraint_len_calculator(&self.items);
+    }
 
-            // Execute delete in background thread
-            thread::spawn(move || {
-                let result = HomebrewScanner::delete_package_with_output(&package, output_sender);
-                let _ = result_sender.send(result);
-            });
+    /// Writes the packages shown, including deletes and refreshes since the
+    /// scan, to the scan cache.
+    fn save_cache(&self) -> Result<(), String> {
+        match self.scanned_at {
+            Some(scanned_at) => cache::save_cache(&self.items, scanned_at),
+            None => Ok(()),
         }
     }
 
-    fn check_delete_progress(&mut self) {
-        // Check for new output lines
-        if let Some(ref receiver) = self.delete_output_receiver {
-            while let Ok(line) = receiver.try_recv() {
-                self.delete_output.push(line);
-                // Keep only the last 20 lines to prevent memory buildup
-                if self.delete_output.len() > 20 {
-                    self.delete_output.remove(0);
-                }
-            }
-        }
+    /// Maps the highlighted table row to its index in `items`.
+    fn selected_index(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|row| self.filtered_indices.get(row).copied())
+    }
 
-        // Check if deletion completed
-        if let Some(ref receiver) = self.delete_result_receiver {
-            if let Ok(result) = receiver.try_recv() {
-                // Clear receivers
-                self.delete_output_receiver = None;
-                self.delete_result_receiver = None;
+    fn is_reviewed(&self, package: &Package) -> bool {
+        self.config.reviewed.contains(&package.name)
+    }
 
-                if let AppState::Deleting(package_index) = self.app_state {
-                    let package_name = self
-                        .items
-                        .get(package_index)
-                        .map(|p| p.name.clone())
-                        .unwrap_or_else(|| "Unknown".to_string());
+    fn is_visible(&self, package: &Package) -> bool {
+        !(self.hide_reviewed && self.is_reviewed(package))
+            && (!self.only_unused || package.unused_since_install())
+            && (!self.only_outdated || package.outdated)
+            && self.idle_filter.matches(package)
+            && self
+                .size_filter
+                .is_none_or(|range| package.size_bytes.is_some_and(|size| range.contains(size)))
+            && (self.filter.is_empty()
+                || package
+                    .name
+                    .to_lowercase()
+                    .contains(&self.filter.to_lowercase()))
+    }
 
-                    match result {
-                        Ok(()) => {
-                            let message =
-                                format!("Successfully deleted package '{}'", package_name);
-                            self.handle_delete_result(package_index, true, message);
-                        }
-                        Err(e) => {
-                            let message = format!("Failed to delete '{}': {}", package_name, e);
-                            self.handle_delete_result(package_index, false, message);
-                        }
+    /// Recomputes the visible rows and keeps the selection and scrollbar in range.
+    fn apply_filter(&mut self) {
+        let threshold = self.config.small_package_threshold_mb * 1024 * 1024;
+        let mut small_count = 0;
+        let mut small_bytes = 0;
+
+        let filtered: Vec<usize> = (0..self.items.len())
+            .filter(|&i| {
+                let package = &self.items[i];
+                if !self.is_visible(package) {
+                    return false;
+                }
+                match package.size_bytes {
+                    Some(size) if self.group_small && size < threshold => {
+                        small_count += 1;
+                        small_bytes += size;
+                        self.small_group_expanded
                     }
+                    _ => true,
                 }
-            }
+            })
+            .collect();
+
+        self.filtered_indices = filtered;
+        self.small_group = (small_count > 0).then_some((small_count, small_bytes));
+
+        let visible = self.visible_row_count();
+        match self.state.selected() {
+            _ if visible == 0 => self.state.select(None),
+            Some(row) if row >= visible => self.state.select(Some(visible - 1)),
+            None => self.state.select(Some(0)),
+            Some(_) => {}
         }
+
+        self.update_scroll_state();
     }
 
-    fn handle_delete_result(&mut self, package_index: usize, success: bool, message: String) {
-        if success {
-            // Remove the package from the list
-            if package_index < self.items.len() {
-                self.items.remove(package_index);
+    /// Lines available for table rows: the screen minus footer and header.
+    fn table_viewport_height(&self) -> usize {
+        // Less the table's borders and header row
+        self.terminal_size.1.saturating_sub(FOOTER_HEIGHT + 3) as usize
+    }
 
-                self.sort_packages_by_usage();
+    /// Table rows that fit on screen at once.
+    fn rows_per_page(&self) -> usize {
+        (self.table_viewport_height() / self.item_height()).max(1)
+    }
 
-                // Update table state
-                if self.items.is_empty() {
-                    self.state.select(None);
-                } else if package_index >= self.items.len() {
-                    self.state.select(Some(self.items.len() - 1));
-                } else {
-                    self.state.select(Some(package_index));
-                }
+    fn update_scroll_state(&mut self) {
+        let visible = self.visible_row_count();
+        self.scroll_state = ScrollbarState::new(visible.saturating_sub(1) * self.item_height())
+            .viewport_content_length(self.table_viewport_height())
+            .position(self.state.selected().unwrap_or(0) * self.item_height());
+    }
 
-                // Recalculate constraints and scroll state
-                self.longest_item_lens = constraint_len_calculator(&self.items);
-                self.scroll_state = ScrollbarState::new(if self.items.is_empty() {
-                    0
-                } else {
-                    (self.items.len() - 1) * ITEM_HEIGHT
-                });
+    /// Keeps the selected row on screen after the terminal changes size.
+    fn handle_resize(&mut self, width: u16, height: u16) {
+        self.terminal_size = (width, height);
+
+        let visible = self.visible_row_count();
+        if let Some(row) = self.state.selected() {
+            if visible == 0 {
+                self.state.select(None);
+            } else {
+                let row = row.min(visible - 1);
+                self.state.select(Some(row));
+
+                let rows_per_page = self.rows_per_page();
+                let offset = self.state.offset_mut();
+                if row < *offset {
+                    *offset = row;
+                } else if row >= *offset + rows_per_page {
+                    *offset = row + 1 - rows_per_page;
+                }
             }
-            self.delete_success = true;
-        } else {
-            self.delete_success = false;
         }
 
-        self.delete_message = Some(message);
-        self.app_state = AppState::Table;
+        self.update_scroll_state();
     }
 
-    fn sort_packages_by_usage(&mut self) {
-        // Simple sort: Only by last accessed time, oldest first
-        self.items.sort_by(|a, b| {
-            match (&a.last_accessed, &b.last_accessed) {
-                (None, None) => std::cmp::Ordering::Equal, // Both never used, keep original order
-                (None, Some(_)) => std::cmp::Ordering::Less, // Never used comes first
-                (Some(_), None) => std::cmp::Ordering::Greater, // Used comes after never used
-                (Some(a_time), Some(b_time)) => a_time.cmp(b_time), // Oldest access time first
-            }
-        });
+    /// Number of table rows, including the small-package summary row if shown.
+    fn visible_row_count(&self) -> usize {
+        self.filtered_indices.len() + usize::from(self.small_group.is_some())
+    }
 
-        // Reset selection to top after sorting
-        if !self.items.is_empty() {
-            self.state.select(Some(0));
-            self.scroll_state = self.scroll_state.position(0);
-        }
+    fn is_small_group_row(&self, row: usize) -> bool {
+        self.small_group.is_some() && row == self.filtered_indices.len()
     }
 
-    fn get_scanning_state(&self) -> Option<ScanningState> {
-        self.scanner.as_ref().map(|s| s.get_state())
+    fn toggle_group_small(&mut self) {
+        self.group_small = !self.group_small;
+        self.small_group_expanded = false;
+        self.apply_filter();
     }
 
-    pub fn next_row(&mut self) {
-        if !matches!(self.app_state, AppState::Table) || self.items.is_empty() {
-            return;
+    fn activate_selected_row(&mut self) {
+        match self.state.selected() {
+            Some(row) if self.is_small_group_row(row) => {
+                self.small_group_expanded = !self.small_group_expanded;
+                self.apply_filter();
+            }
+            _ => self.select_package(),
         }
+    }
 
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    fn toggle_reviewed(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
         };
 
-        self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        let name = self.items[index].name.clone();
+        if !self.config.reviewed.remove(&name) {
+            self.config.reviewed.insert(name);
+        }
+        if let Err(e) = self.config.save_config() {
+            self.status_message = Some(e);
+        }
+        self.apply_filter();
     }
 
-    pub fn previous_row(&mut self) {
-        if !matches!(self.app_state, AppState::Table) || self.items.is_empty() {
+    fn is_kept(&self, package: &Package) -> bool {
+        self.config.keep.contains(&package.name)
+    }
+
+    fn is_pinned(&self, package: &Package) -> bool {
+        self.config.pinned.contains(&package.name)
+    }
+
+    /// Pins or unpins the selected package, or the one whose details are open.
+    /// Pinning also drops its batch mark.
+    fn toggle_pin(&mut self) {
+        let index = match self.app_state {
+            AppState::PackageSelected(idx) => idx,
+            _ => match self.selected_index() {
+                Some(index) => index,
+                None => return,
+            },
+        };
+        let Some(package) = self.items.get(index) else {
             return;
-        }
+        };
 
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+        let name = package.name.clone();
+        let pinned = !self.config.pinned.remove(&name);
+        if pinned {
+            self.config.pinned.insert(name.clone());
+            self.marked.remove(&index);
+        }
+        self.status_success = true;
+        self.status_message = Some(if pinned {
+            format!("Pinned {}: it can't be deleted until unpinned", name)
+        } else {
+            format!("Unpinned {}", name)
+        });
+        if let Err(e) = self.config.save_config() {
+            self.status_success = false;
+            self.status_message = Some(e);
+        }
+    }
+
+    /// Reports and returns true when `package_index` is pinned, so a delete
+    /// or mark of it can be refused.
+    fn refuse_pinned(&mut self, package_index: usize) -> bool {
+        let Some(package) = self.items.get(package_index) else {
+            return false;
         };
-        self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        if !self.is_pinned(package) {
+            return false;
+        }
+        self.status_success = false;
+        self.status_message = Some(format!(
+            "{} is pinned; press p to unpin it before deleting",
+            package.name
+        ));
+        true
     }
 
-    pub fn next_column(&mut self) {
-        if matches!(self.app_state, AppState::Table) {
-            self.state.select_next_column();
+    fn toggle_keep(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+
+        let name = self.items[index].name.clone();
+        if !self.config.keep.remove(&name) {
+            self.config.keep.insert(name);
+        }
+        if let Err(e) = self.config.save_config() {
+            self.status_message = Some(e);
         }
     }
 
-    pub fn previous_column(&mut self) {
-        if matches!(self.app_state, AppState::Table) {
-            self.state.select_previous_column();
+    /// Blank lines above and below each row's text; compact rows have none.
+    fn vertical_padding(&self) -> usize {
+        match self.density {
+            TableDensity::Comfortable => {
+                self.config.row_padding_vertical.min(MAX_ROW_PADDING) as usize
+            }
+            TableDensity::Compact => 0,
         }
     }
 
-    pub fn next_color(&mut self) {
-        self.color_index = (self.color_index + 1) % PALETTES.len();
+    /// Spaces either side of each cell's text.
+    fn horizontal_padding(&self) -> u16 {
+        self.config.row_padding_horizontal.min(MAX_ROW_PADDING)
     }
 
-    pub fn previous_color(&mut self) {
-        let count = PALETTES.len();
-        self.color_index = (self.color_index + count - 1) % count;
+    fn item_height(&self) -> usize {
+        2 * self.vertical_padding() + 1
     }
 
-    pub fn set_colors(&mut self) {
-        self.colors = TableColors::new(&PALETTES[self.color_index]);
+    /// Wraps a cell's line in the configured vertical padding.
+    fn padded_cell<'a>(&self, line: Line<'a>) -> Cell<'a> {
+        let padding = self.vertical_padding();
+        let mut lines = vec![Line::default(); padding];
+        lines.push(line);
+        lines.resize(2 * padding + 1, Line::default());
+        Cell::from(Text::from(lines))
     }
 
-    pub fn toggle_pause(&mut self) {
-        if let Some(ref scanner) = self.scanner {
-            scanner.toggle_pause();
+    fn toggle_density(&mut self) {
+        self.density = match self.density {
+            TableDensity::Comfortable => TableDensity::Compact,
+            TableDensity::Compact => TableDensity::Comfortable,
+        };
+        self.apply_filter();
+    }
+
+    fn toggle_only_unused(&mut self) {
+        self.only_unused = !self.only_unused;
+        self.apply_filter();
+    }
+
+    fn toggle_only_outdated(&mut self) {
+        self.only_outdated = !self.only_outdated;
+        self.apply_filter();
+    }
+
+    fn cycle_idle_filter(&mut self) {
+        self.idle_filter = self.idle_filter.next();
+        self.apply_filter();
+    }
+
+    fn toggle_hide_reviewed(&mut self) {
+        self.hide_reviewed = !self.hide_reviewed;
+        self.apply_filter();
+    }
+
+    fn select_package(&mut self) {
+        if let Some(selected_index) = self.selected_index() {
+            self.open_screen(AppState::PackageSelected(selected_index));
+            self.fetch_reported_size(selected_index);
+            self.fetch_deps(selected_index);
+            if self.shows_extended_details(&self.items[selected_index]) {
+                self.fetch_size_breakdown(selected_index);
+            }
         }
     }
 
-    fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        loop {
-            terminal.draw(|frame| self.draw(frame))?;
+    /// Large packages get the extended details view automatically.
+    fn shows_extended_details(&self, package: &Package) -> bool {
+        let threshold = self.config.details_expand_threshold_mb * 1024 * 1024;
+        self.full_details || package.size_bytes.is_some_and(|size| size >= threshold)
+    }
 
-            // Update scanning progress
-            if matches!(self.app_state, AppState::Scanning) {
-                self.update_scan();
+    fn toggle_full_details(&mut self, package_index: usize) {
+        self.full_details = !self.full_details;
+        if self.shows_extended_details(&self.items[package_index]) {
+            self.fetch_size_breakdown(package_index);
+        }
+    }
+
+    /// Measures each of the package's paths in the background.
+    fn fetch_size_breakdown(&mut self, package_index: usize) {
+        let package = self.items[package_index].clone();
+        if matches!(self.size_breakdown, Some((ref name, _)) if *name == package.name) {
+            return;
+        }
+
+        self.size_breakdown = None;
+        let (sender, receiver) = mpsc::channel();
+        self.size_breakdown_receiver = Some(receiver);
+        thread::spawn(move || {
+            let sizes = HomebrewScanner::size_breakdown(&package.paths);
+            let _ = sender.send((package.name, sizes));
+        });
+    }
+
+    fn check_size_breakdown(&mut self) {
+        if let Some(ref receiver) = self.size_breakdown_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.size_breakdown = Some(result);
+                self.size_breakdown_receiver = None;
+            }
+        }
+    }
+
+    /// Asks `brew info` for its size figure in the background.
+    fn fetch_reported_size(&mut self, package_index: usize) {
+        let package = self.items[package_index].clone();
+        if matches!(self.reported_size, Some((ref name, _)) if *name == package.name) {
+            return;
+        }
+
+        self.reported_size = None;
+        let (sender, receiver) = mpsc::channel();
+        self.reported_size_receiver = Some(receiver);
+        thread::spawn(move || {
+            let size = HomebrewScanner::fetch_reported_size(&package);
+            let _ = sender.send((package.name, size));
+        });
+    }
+
+    fn check_reported_size(&mut self) {
+        if let Some(ref receiver) = self.reported_size_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.reported_size = Some(result);
+                self.reported_size_receiver = None;
             }
+        }
+    }
 
-            if matches!(self.app_state, AppState::Deleting(_)) {
-                self.check_delete_progress();
+    /// Runs `brew deps` for the details view, once per package.
+    fn fetch_deps(&mut self, package_index: usize) {
+        self.deps_scroll = 0;
+        let package = self.items[package_index].clone();
+        if matches!(self.current_deps, Some((ref name, Ok(_))) if *name == package.name) {
+            return;
+        }
+
+        self.current_deps = None;
+        let (sender, receiver) = mpsc::channel();
+        self.deps_receiver = Some(receiver);
+        thread::spawn(move || {
+            let deps = HomebrewScanner::fetch_deps(&package);
+            let _ = sender.send((package.name, deps));
+        });
+    }
+
+    fn check_deps(&mut self) {
+        if let Some(ref receiver) = self.deps_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.current_deps = Some(result);
+                self.deps_receiver = None;
             }
+        }
+    }
 
-            // Handle events with timeout for responsive UI
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        let shift_pressed = key.modifiers.contains(KeyModifiers::SHIFT);
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                            KeyCode::Char(' ') => match self.app_state {
-                                AppState::Table => self.start_scanning(),
-                                AppState::Scanning => self.toggle_pause(),
-                                AppState::ScanComplete => self.app_state = AppState::Table,
-                                AppState::PackageSelected(_) => self.app_state = AppState::Table,
-                                AppState::ConfirmDelete(_) => self.app_state = AppState::Table,
-                                AppState::Deleting(_) => {}
-                            },
-                            KeyCode::Enter => match self.app_state {
-                                AppState::Table => self.select_package(),
-                                AppState::ScanComplete => self.app_state = AppState::Table,
-                                AppState::PackageSelected(_) => self.app_state = AppState::Table,
-                                AppState::ConfirmDelete(idx) => self.execute_delete(idx),
-                                _ => {}
-                            },
-                            KeyCode::Char('d') | KeyCode::Delete => match self.app_state {
-                                AppState::Table => self.delete_selected_package(),
-                                AppState::PackageSelected(idx) => self.confirm_delete(idx),
-                                _ => {}
-                            },
-                            KeyCode::Char('r') => {
-                                if matches!(self.app_state, AppState::Table) {
-                                    self.start_scanning();
-                                }
-                            }
-                            KeyCode::Char('y') => {
-                                if let AppState::ConfirmDelete(idx) = self.app_state {
-                                    self.execute_delete(idx);
-                                }
-                            }
-                            KeyCode::Char('n') => {
-                                if matches!(self.app_state, AppState::ConfirmDelete(_)) {
-                                    self.app_state = AppState::Table;
-                                }
-                            }
-                            KeyCode::Char('j') | KeyCode::Down => self.next_row(),
-                            KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
-                            KeyCode::Char('l') | KeyCode::Right if shift_pressed => {
-                                self.next_color()
-                            }
-                            KeyCode::Char('h') | KeyCode::Left if shift_pressed => {
-                                self.previous_color();
-                            }
-                            KeyCode::Char('l') | KeyCode::Right => self.next_column(),
-                            KeyCode::Char('h') | KeyCode::Left => self.previous_column(),
-                            _ => {}
-                        }
-                    }
+    /// Indices of the largest packages with a known size, biggest first.
+    fn top_sizes(&self) -> Vec<usize> {
+        let mut sized: Vec<usize> = (0..self.items.len())
+            .filter(|&i| self.items[i].size_bytes.is_some())
+            .collect();
+        sized.sort_by_key(|&i| std::cmp::Reverse(self.items[i].size_bytes));
+        sized.truncate(TOP_SIZES);
+        sized
+    }
+
+    /// Moves the highlight on the top space consumers screen by `delta` rows.
+    fn move_top_sizes(&mut self, delta: isize) {
+        if let AppState::TopSizes(row) = self.app_state {
+            let last = self.top_sizes().len().saturating_sub(1);
+            self.app_state = AppState::TopSizes(row.saturating_add_signed(delta).min(last));
+        }
+    }
+
+    /// Enter on the top space consumers screen: back to the table with that
+    /// package selected.
+    fn jump_to_top_size(&mut self, row: usize) {
+        let Some(name) = self
+            .top_sizes()
+            .get(row)
+            .map(|&index| self.items[index].name.clone())
+        else {
+            return;
+        };
+        match self.back_stack.pop() {
+            Some(AppState::ScanComplete) => self.show_results(),
+            _ => self.app_state = AppState::Table,
+        }
+        if self
+            .filtered_indices
+            .iter()
+            .any(|&index| self.items[index].name == name)
+        {
+            self.select_by_name(&name);
+        } else {
+            self.status_message = Some(format!("{} is hidden by the current filters", name));
+            self.status_success = false;
+        }
+    }
+
+    /// Moves the dependency list in the details view by `delta` rows.
+    fn scroll_deps(&mut self, delta: isize) {
+        let count = match self.current_deps {
+            Some((_, Ok(ref deps))) => deps.len(),
+            _ => 0,
+        };
+        let last = count.saturating_sub(DEPS_ROWS);
+        self.deps_scroll = self.deps_scroll.saturating_add_signed(delta).min(last);
+    }
+
+    fn verify_package(&mut self, package_index: usize) {
+        if let Some(package) = self.items.get(package_index) {
+            let missing = HomebrewScanner::find_missing_paths(package);
+            self.verify_result = Some((package.name.clone(), missing));
+        }
+    }
+
+    fn reveal_package(&mut self, package_index: usize) {
+        if let Some(package) = self.items.get(package_index) {
+            let result = match package.last_accessed_path {
+                Some(ref path) => HomebrewScanner::reveal_in_file_manager(path),
+                None => Err("No install path recorded for this package".to_string()),
+            };
+            let result = result.map(|()| "Opened in file manager");
+            self.open_result = Some((package.name.clone(), result));
+        }
+    }
+
+    fn open_homepage(&mut self, package_index: usize) {
+        if let Some(package) = self.items.get(package_index) {
+            let result = HomebrewScanner::open_homepage(package).map(|()| "Opened homepage");
+            self.open_result = Some((package.name.clone(), result));
+        }
+    }
+
+    /// `N` in the table. Not while an uninstall runs in the background, whose
+    /// result would be read with the new setting.
+    fn toggle_dry_run(&mut self) {
+        if self.busy_with_background_delete() {
+            return;
+        }
+        self.dry_run = !self.dry_run;
+        self.status_success = true;
+        self.status_message = Some(if self.dry_run {
+            "Dry run on: deletes only list what they would remove".to_string()
+        } else {
+            "Dry run off: deletes uninstall again".to_string()
+        });
+    }
+
+    /// Appended to delete titles so a dry run can't be mistaken for a real one.
+    fn dry_run_tag(&self) -> &'static str {
+        if self.dry_run {
+            " (dry run)"
+        } else {
+            ""
+        }
+    }
+
+    /// Refuses to start another brew command while a detached uninstall is
+    /// still running, since it owns the delete channels.
+    fn busy_with_background_delete(&mut self) -> bool {
+        let Some(ref name) = self.background_delete else {
+            return false;
+        };
+        self.status_success = false;
+        self.status_message = Some(format!("Still uninstalling '{}' in the background", name));
+        true
+    }
+
+    fn confirm_delete(&mut self, package_index: usize) {
+        if self.busy_with_background_delete() || self.refuse_pinned(package_index) {
+            return;
+        }
+        self.stop_watching();
+        self.confirm_hold = None;
+        self.delete_zap = false;
+        self.open_screen(AppState::ConfirmDelete(package_index));
+
+        let Some(package) = self.items.get(package_index) else {
+            return;
+        };
+        self.confirm_dependents = package.dependents.clone();
+        self.dependents_receiver = None;
+        // Casks can't be dependencies; for formulae, ask brew for a current answer
+        if package.package_type == PackageType::Formula {
+            let name = package.name.clone();
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let _ = sender.send(HomebrewScanner::fetch_uses(&name));
+            });
+            self.dependents_receiver = Some(receiver);
+        }
+    }
+
+    /// Replaces the scan's dependents with `brew uses`' answer. If brew
+    /// fails, the scan's list stands.
+    fn check_dependents(&mut self) {
+        if let Some(ref receiver) = self.dependents_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                if let Ok(dependents) = result {
+                    self.confirm_dependents = dependents;
                 }
+                self.dependents_receiver = None;
             }
         }
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
-        self.set_colors();
+    /// Handles `y`/`Enter` on the delete confirmation, which either deletes right
+    /// away or, with `hold_to_confirm`, only once the key has been held long enough.
+    fn press_confirm_delete(&mut self, package_index: usize) {
+        if !self.config.hold_to_confirm {
+            self.execute_delete(package_index);
+            return;
+        }
 
-        match self.app_state {
-            AppState::Scanning => self.render_scanning_ui(frame),
-            AppState::ScanComplete => self.render_scan_complete_ui(frame),
-            AppState::PackageSelected(idx) => self.render_package_details(frame, idx),
-            AppState::ConfirmDelete(idx) => self.render_confirm_delete(frame, idx),
-            AppState::Deleting(idx) => self.render_deleting(frame, idx),
-            AppState::Table => {
-                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(6)]);
-                let rects = vertical.split(frame.area());
+        let now = Instant::now();
+        let started = match self.confirm_hold {
+            Some((started, last)) if now.duration_since(last) <= HOLD_RELEASE_GAP => started,
+            _ => now,
+        };
+        self.confirm_hold = Some((started, now));
 
-                self.render_table(frame, rects[0]);
-                if !self.items.is_empty() {
-                    self.render_scrollbar(frame, rects[0]);
+        if now.duration_since(started) >= HOLD_TO_CONFIRM {
+            self.confirm_hold = None;
+            self.execute_delete(package_index);
+        }
+    }
+
+    /// Resets the hold once key repeats stop arriving.
+    fn check_confirm_hold(&mut self) {
+        if let Some((_, last)) = self.confirm_hold {
+            if last.elapsed() > HOLD_RELEASE_GAP {
+                self.confirm_hold = None;
+            }
+        }
+    }
+
+    fn confirm_hold_progress(&self) -> f64 {
+        self.confirm_hold.map_or(0.0, |(started, _)| {
+            (started.elapsed().as_secs_f64() / HOLD_TO_CONFIRM.as_secs_f64()).min(1.0)
+        })
+    }
+
+    fn delete_selected_package(&mut self) {
+        if let Some(selected_index) = self.selected_index() {
+            self.confirm_delete(selected_index);
+        }
+    }
+
+    fn execute_delete(&mut self, package_index: usize) {
+        self.execute_delete_with(
+            package_index,
+            UninstallOptions {
+                zap: self.delete_zap,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// `z` on the delete confirmation; only casks can be zapped.
+    fn toggle_delete_zap(&mut self, package_index: usize) {
+        if self
+            .items
+            .get(package_index)
+            .is_some_and(|package| package.package_type == PackageType::Cask)
+        {
+            self.delete_zap = !self.delete_zap;
+            self.confirm_hold = None;
+        }
+    }
+
+    fn execute_delete_with(&mut self, package_index: usize, options: UninstallOptions) {
+        let options = UninstallOptions {
+            dry_run: self.dry_run,
+            ..options
+        };
+        if self.refuse_pinned(package_index) {
+            return;
+        }
+        if package_index < self.items.len() {
+            self.app_state = AppState::Deleting(package_index);
+            let package = self.items[package_index].clone();
+            self.retry_target = None;
+
+            // Clear previous output
+            self.clear_output();
+            self.delete_cancel = options.cancel.clone();
+
+            // Create channels for output and result
+            let (output_sender, output_receiver) = mpsc::channel();
+            let (result_sender, result_receiver) = mpsc::channel();
+
+            self.delete_output_receiver = Some(output_receiver);
+            self.delete_result_receiver = Some(result_receiver);
+
+            // Execute delete in background thread
+            thread::spawn(move || {
+                let result = HomebrewScanner::new().delete_package_with_output(
+                    &package,
+                    &options,
+                    output_sender,
+                );
+                let _ = result_sender.send(result);
+            });
+        }
+    }
+
+    /// Kills a running single-package uninstall and returns to the table.
+    ///
+    /// If brew has already exited the result is left to arrive normally, so a
+    /// finished uninstall is never shown as still installed.
+    fn cancel_delete(&mut self) {
+        let AppState::Deleting(package_index) = self.app_state else {
+            return;
+        };
+        if !self.delete_cancel.cancel() {
+            return;
+        }
+
+        self.delete_output_receiver = None;
+        self.delete_result_receiver = None;
+        self.clear_output();
+
+        let name = self
+            .items
+            .get(package_index)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string());
+        self.status_success = false;
+        self.status_message = Some(format!(
+            "Cancelled uninstalling '{}'. Press r to refresh if it was partly removed",
+            name
+        ));
+        self.app_state = AppState::Table;
+    }
+
+    fn drain_delete_output(&mut self) {
+        if let Some(ref receiver) = self.delete_output_receiver {
+            while let Ok(line) = receiver.try_recv() {
+                self.delete_output.push(line);
+                // Scrolled up: keep the same lines in view as the tail grows
+                if self.output_scroll_back > 0 {
+                    self.output_scroll_back += 1;
                 }
-                self.render_footer(frame, rects[1]);
             }
         }
     }
-    fn render_scanning_ui(&self, frame: &mut Frame) {
-        let scanning_state = self.get_scanning_state().unwrap_or_else(ScanningState::new);
 
-        let scanning_block = Block::default()
-            .title("🔍 Homebrew Package Scanner")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(self.colors.footer_border_color))
+    fn clear_output(&mut self) {
+        self.delete_output.clear();
+        self.output_scroll_back = 0;
+    }
+
+    /// Screens that stream `delete_output`.
+    fn showing_output(&self) -> bool {
+        matches!(
+            self.app_state,
+            AppState::Deleting(_) | AppState::BatchDeleting | AppState::RunningMaintenance(_)
+        )
+    }
+
+    /// Lines of command output on screen at once: the terminal minus the
+    /// output screen's borders, margins and other rows.
+    fn output_viewport_height(&self) -> usize {
+        let inner = self.terminal_size.1.saturating_sub(4) as usize;
+        let other_rows = match self.app_state {
+            // Per-package status list above, controls below
+            AppState::BatchDeleting => inner * 2 / 5 + 1,
+            // Package, blank line, controls
+            AppState::Deleting(_) => 3,
+            // Command and controls, blank line
+            _ => 2,
+        };
+        // Less the output block's borders
+        inner.saturating_sub(other_rows + 2).max(1)
+    }
+
+    /// Scrolls the command output `delta` lines towards its tail.
+    fn scroll_output(&mut self, delta: isize) {
+        let max = self
+            .delete_output
+            .len()
+            .saturating_sub(self.output_viewport_height());
+        self.output_scroll_back =
+            (self.output_scroll_back as isize - delta).clamp(0, max as isize) as usize;
+    }
+
+    fn check_delete_progress(&mut self) {
+        // Check for new output lines
+        self.drain_delete_output();
+
+        // Check if deletion completed
+        if let Some(ref receiver) = self.delete_result_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                // Clear receivers
+                self.delete_output_receiver = None;
+                self.delete_result_receiver = None;
+
+                if let AppState::Deleting(package_index) = self.app_state {
+                    let package_name = self
+                        .items
+                        .get(package_index)
+                        .map(|p| p.name.clone())
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    self.finish_delete(package_index, package_name, result);
+                }
+            }
+        }
+    }
+
+    /// The single uninstall has yet to report its result.
+    fn delete_running(&self) -> bool {
+        self.delete_result_receiver.is_some()
+    }
+
+    fn finish_delete(
+        &mut self,
+        package_index: usize,
+        package_name: String,
+        result: Result<(), String>,
+    ) {
+        match result {
+            Ok(()) if self.dry_run => {
+                // Nothing was removed, so the package stays listed. The output
+                // stays up until Enter/Space, as it is the point of a dry run.
+                self.status_success = true;
+                self.status_message = Some(format!(
+                    "Dry run: '{}' was not removed (N to turn dry run off)",
+                    package_name
+                ));
+            }
+            Ok(()) => {
+                // Read before the package leaves `items`
+                let freed = self.items.get(package_index).and_then(|p| p.size_bytes);
+                let message = format!(
+                    "Successfully deleted package '{}'{}",
+                    package_name,
+                    freed
+                        .map(|bytes| format!(", freed {}", format_size(bytes)))
+                        .unwrap_or_default()
+                );
+                self.handle_delete_result(package_index, true, message);
+            }
+            Err(e) => {
+                let message = format!("Failed to delete '{}': {}", package_name, e);
+                self.handle_delete_result(package_index, false, message);
+                self.retry_target = Some(package_name);
+            }
+        }
+    }
+
+    /// Leaves the live output of a running uninstall for the table. The
+    /// uninstall carries on and both receivers stay open so brew's thread can
+    /// still send; its output is no longer read.
+    fn stop_watching_delete(&mut self) {
+        let AppState::Deleting(package_index) = self.app_state else {
+            return;
+        };
+        let Some(name) = self.items.get(package_index).map(|p| p.name.clone()) else {
+            return;
+        };
+
+        self.clear_output();
+        self.status_success = true;
+        self.status_message = Some(format!("Uninstalling '{}' in the background", name));
+        self.background_delete = Some(name);
+        self.app_state = AppState::Table;
+    }
+
+    /// Picks up the result of a detached uninstall. Only checked on the table,
+    /// so removing the package never shifts the index of an open details view.
+    /// The package is found by name since the list may have been re-sorted.
+    fn check_background_delete(&mut self) {
+        let Some(ref receiver) = self.delete_result_receiver else {
+            return;
+        };
+        let Ok(result) = receiver.try_recv() else {
+            return;
+        };
+        self.delete_output_receiver = None;
+        self.delete_result_receiver = None;
+        let Some(name) = self.background_delete.take() else {
+            return;
+        };
+
+        match self.items.iter().position(|p| p.name == name) {
+            Some(package_index) => self.finish_delete(package_index, name, result),
+            None => {
+                // A rescan replaced the list; just report the outcome
+                self.status_success = result.is_ok();
+                self.status_message = Some(match result {
+                    Ok(()) => format!("Successfully deleted package '{}'", name),
+                    Err(e) => format!("Failed to delete '{}': {}", name, e),
+                });
+            }
+        }
+    }
+
+    /// The package shown below `package_index`, or above it for the last row.
+    fn neighbour_of(&self, package_index: usize) -> Option<String> {
+        let row = self
+            .filtered_indices
+            .iter()
+            .position(|&i| i == package_index)?;
+        let next = self.filtered_indices.get(row + 1).or_else(|| {
+            row.checked_sub(1)
+                .and_then(|prev| self.filtered_indices.get(prev))
+        })?;
+        Some(self.items[*next].name.clone())
+    }
+
+    fn handle_delete_result(&mut self, package_index: usize, success: bool, message: String) {
+        if success {
+            // Remove the package from the list
+            if package_index < self.items.len() {
+                let neighbour = if self.config.keep_place_on_delete {
+                    self.neighbour_of(package_index)
+                } else {
+                    None
+                };
+                // The deleted package's row, where the cursor stays by default
+                let row = self
+                    .filtered_indices
+                    .iter()
+                    .position(|&i| i == package_index);
+
+                // Removed inside so marks follow the packages that shifted up
+                self.preserving_marks(|app| {
+                    app.items.remove(package_index);
+                    app.sort_items();
+                });
+                self.longest_item_lens = constraint_len_calculator(&self.items);
+                self.apply_filter();
+
+                match neighbour {
+                    Some(name) => self.select_by_name(&name),
+                    None => self.select_row_near(row),
+                }
+            }
+            self.status_success = true;
+
+            if let Some(action) = self.config.post_delete_action.maintenance_action() {
+                self.chained_delete_message = Some(message);
+                self.run_maintenance(action);
+                return;
+            }
+        } else {
+            self.status_success = false;
+        }
+
+        self.status_message = Some(message);
+        self.app_state = AppState::Table;
+    }
+
+    fn open_maintenance_menu(&mut self) {
+        if self.busy_with_background_delete() {
+            return;
+        }
+        self.stop_watching();
+        self.measure_cache_size();
+        self.open_screen(AppState::MaintenanceMenu);
+    }
+
+    /// Measures the download cache in the background; `check_cache_size`
+    /// picks up the result.
+    fn measure_cache_size(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(HomebrewScanner::cache_size());
+        });
+
+        self.cache_size = None;
+        self.cache_size_receiver = Some(receiver);
+    }
+
+    fn check_cache_size(&mut self) {
+        if let Some(ref receiver) = self.cache_size_receiver {
+            if let Ok(size) = receiver.try_recv() {
+                if let Some(before) = self.pruned_from.take() {
+                    let after = size.as_ref().map_or(0, |(_, size)| *size);
+                    self.status_success = true;
+                    self.status_message = Some(format!(
+                        "{} finished, freed {}",
+                        MaintenanceAction::PruneCache.label(),
+                        format_size(before.saturating_sub(after))
+                    ));
+                }
+                self.cache_size = Some(size);
+                self.cache_size_receiver = None;
+            }
+        }
+    }
+
+    /// Starts a dry run of `action` and shows its confirm screen.
+    fn confirm_maintenance(&mut self, action: MaintenanceAction) {
+        // Brew doesn't list pruned links in a dry run, but the scan already found them
+        if action == MaintenanceAction::PrunePrefix {
+            self.maintenance_preview = Some(Ok(MaintenancePreview {
+                items: self
+                    .broken_links
+                    .iter()
+                    .map(|link| link.path.display().to_string())
+                    .collect(),
+                freed: None,
+            }));
+            self.maintenance_preview_receiver = None;
+            self.open_screen(AppState::ConfirmMaintenance(action));
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let dry_run = action.clone();
+        thread::spawn(move || {
+            let _ = sender.send(HomebrewScanner::preview_maintenance(&dry_run));
+        });
+
+        self.maintenance_preview = None;
+        self.maintenance_preview_receiver = Some(receiver);
+        self.open_screen(AppState::ConfirmMaintenance(action));
+    }
+
+    fn check_maintenance_preview(&mut self) {
+        if let Some(ref receiver) = self.maintenance_preview_receiver {
+            if let Ok(preview) = receiver.try_recv() {
+                if let (AppState::ConfirmMaintenance(MaintenanceAction::Autoremove), Ok(preview)) =
+                    (&self.app_state, &preview)
+                {
+                    self.autoremovable = preview.items.iter().cloned().collect();
+                }
+                self.maintenance_preview = Some(preview);
+                self.maintenance_preview_receiver = None;
+            }
+        }
+    }
+
+    /// `o`: dry-runs `brew autoremove` straight from the table. The orphaned
+    /// dependencies it finds stay marked in the table afterwards.
+    fn preview_autoremove(&mut self) {
+        if self.busy_with_background_delete() {
+            return;
+        }
+        self.stop_watching();
+        self.confirm_maintenance(MaintenanceAction::Autoremove);
+    }
+
+    /// Runs the action being confirmed once its dry run has shown something to do.
+    fn execute_maintenance(&mut self) {
+        let AppState::ConfirmMaintenance(ref action) = self.app_state else {
+            return;
+        };
+        let action = action.clone();
+        let has_items =
+            matches!(self.maintenance_preview, Some(Ok(ref preview)) if !preview.items.is_empty());
+        if !has_items {
+            return;
+        }
+
+        self.run_maintenance(action);
+    }
+
+    /// `u` in details: runs `brew upgrade` for the package with streamed output.
+    fn upgrade_package(&mut self, package_index: usize) {
+        if self.busy_with_background_delete() {
+            return;
+        }
+        let Some(package) = self.items.get(package_index) else {
+            return;
+        };
+        let action = MaintenanceAction::Upgrade(package.name.clone(), package.package_type.clone());
+        self.stop_watching();
+        self.run_maintenance(action);
+    }
+
+    /// Runs `action` with streamed output, without a dry run first.
+    fn run_maintenance(&mut self, action: MaintenanceAction) {
+        self.app_state = AppState::RunningMaintenance(action.clone());
+        self.clear_output();
+
+        let (output_sender, output_receiver) = mpsc::channel();
+        let (result_sender, result_receiver) = mpsc::channel();
+        self.delete_output_receiver = Some(output_receiver);
+        self.delete_result_receiver = Some(result_receiver);
+
+        let cancel = CancelHandle::default();
+        self.maintenance_cancel = cancel.clone();
+        thread::spawn(move || {
+            let result =
+                HomebrewScanner::run_maintenance_with_output(action, &cancel, output_sender);
+            let _ = result_sender.send(result);
+        });
+    }
+
+    /// Scanned sizes of the formulae in `names`: the total of those known and
+    /// how many had one.
+    fn scanned_sizes(&self, names: &[String]) -> (u64, usize) {
+        names
+            .iter()
+            .filter_map(|name| {
+                self.items
+                    .iter()
+                    .find(|p| p.package_type == PackageType::Formula && p.name == *name)?
+                    .size_bytes
+            })
+            .fold((0, 0), |(total, known), size| (total + size, known + 1))
+    }
+
+    /// Drops the formulae in `names` whose files are gone after an autoremove.
+    /// Returns how many were removed and, if any were sized, the space freed.
+    fn drop_removed_formulae(&mut self, names: &[String]) -> (usize, Option<u64>) {
+        let removed: Vec<String> = self
+            .items
+            .iter()
+            .filter(|p| p.package_type == PackageType::Formula && names.contains(&p.name))
+            .filter(|p| {
+                !p.paths.is_empty() && HomebrewScanner::find_missing_paths(p).len() == p.paths.len()
+            })
+            .map(|p| p.name.clone())
+            .collect();
+        let (freed, known) = self.scanned_sizes(&removed);
+
+        if !removed.is_empty() {
+            self.preserving_marks(|app| app.items.retain(|p| !removed.contains(&p.name)));
+            self.longest_item_lens = constraint_len_calculator(&self.items);
+            self.apply_filter();
+        }
+        (removed.len(), (known > 0).then_some(freed))
+    }
+
+    fn cancel_maintenance(&mut self) {
+        let AppState::RunningMaintenance(ref action) = self.app_state else {
+            return;
+        };
+        let label = action.label();
+        if !self.maintenance_cancel.cancel() {
+            return;
+        }
+
+        self.delete_output_receiver = None;
+        self.delete_result_receiver = None;
+        self.clear_output();
+        self.maintenance_preview = None;
+
+        let cancelled = format!("Cancelled {}", label.to_lowercase());
+        self.status_success = false;
+        self.status_message = Some(match self.chained_delete_message.take() {
+            Some(deleted) => format!("{} | {}", deleted, cancelled),
+            None => cancelled,
+        });
+        self.app_state = AppState::Table;
+    }
+
+    fn check_maintenance_progress(&mut self) {
+        self.drain_delete_output();
+
+        let AppState::RunningMaintenance(ref action) = self.app_state else {
+            return;
+        };
+        let action = action.clone();
+        let Some(Ok(result)) = self.delete_result_receiver.as_ref().map(|r| r.try_recv()) else {
+            return;
+        };
+
+        self.delete_output_receiver = None;
+        self.delete_result_receiver = None;
+        let preview = self.maintenance_preview.take();
+
+        match result {
+            Ok(()) => {
+                if action == MaintenanceAction::PrunePrefix {
+                    self.broken_links.clear();
+                }
+                if let MaintenanceAction::Upgrade(ref name, _) = action {
+                    // Pick up the new version and size; the refresh reports when done
+                    if let Some(index) = self.items.iter().position(|p| p.name == *name) {
+                        self.items[index].outdated = false;
+                        self.refresh_package_at(index);
+                    }
+                }
+                let autoremoved = match (&action, preview) {
+                    (MaintenanceAction::Autoremove, Some(Ok(preview))) => {
+                        Some(self.drop_removed_formulae(&preview.items))
+                    }
+                    _ => None,
+                };
+                if action == MaintenanceAction::Autoremove {
+                    self.autoremovable.clear();
+                }
+                self.status_success = true;
+                self.status_message = Some(match action {
+                    MaintenanceAction::Autoremove => match autoremoved {
+                        Some((removed, freed)) => format!(
+                            "{} finished: removed {} package{}{}",
+                            action.label(),
+                            removed,
+                            if removed == 1 { "" } else { "s" },
+                            freed
+                                .map(|bytes| format!(", freed {}", format_size(bytes)))
+                                .unwrap_or_default()
+                        ),
+                        None => {
+                            format!("{} finished. Press r to refresh the list", action.label())
+                        }
+                    },
+                    MaintenanceAction::RestoreBrewfile(_) => {
+                        format!("{} finished. Press r to refresh the list", action.label())
+                    }
+                    MaintenanceAction::Upgrade(ref name, _) => format!("Upgraded {}", name),
+                    MaintenanceAction::PruneCache => match self.cache_size {
+                        // Measured off the UI thread; the space freed is reported when done
+                        Some(Ok((_, before))) => {
+                            self.pruned_from = Some(before);
+                            self.measure_cache_size();
+                            format!("{} finished, measuring the space freed...", action.label())
+                        }
+                        _ => format!("{} finished", action.label()),
+                    },
+                    _ => format!("{} finished", action.label()),
+                });
+            }
+            Err(e) => {
+                self.status_success = false;
+                self.status_message = Some(format!("{} failed: {}", action.label(), e));
+            }
+        }
+        if let Some(deleted) = self.chained_delete_message.take() {
+            self.status_message = self
+                .status_message
+                .take()
+                .map(|message| format!("{} | {}", deleted, message));
+        }
+        self.app_state = AppState::Table;
+    }
+
+    fn toggle_watching(&mut self) {
+        if self.watching_since.is_some() {
+            self.stop_watching();
+        } else if !self.items.is_empty() {
+            self.watching_since = Some(Instant::now());
+        }
+    }
+
+    /// Leaves watch mode, dropping any refresh still in flight.
+    fn stop_watching(&mut self) {
+        self.watching_since = None;
+        self.watch_receiver = None;
+    }
+
+    fn watch_interval(&self) -> Duration {
+        Duration::from_secs(self.config.watch_interval_secs.max(1))
+    }
+
+    /// Starts a background access-time refresh once the watch interval has passed.
+    fn check_watch(&mut self) {
+        let Some(since) = self.watching_since else {
+            return;
+        };
+
+        if let Some(ref receiver) = self.watch_receiver {
+            if let Ok(refreshed) = receiver.try_recv() {
+                self.watch_receiver = None;
+                self.apply_access_times(refreshed);
+            }
+            return;
+        }
+
+        if since.elapsed() < self.watch_interval() {
+            return;
+        }
+
+        let mut packages = self.items.clone();
+        let scanner = HomebrewScanner::new().with_options(self.config.scan_options());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            scanner.refresh_access_times(&mut packages);
+            let _ = sender.send(packages);
+        });
+        self.watch_receiver = Some(receiver);
+        self.watching_since = Some(Instant::now());
+    }
+
+    /// Copies refreshed access times onto the matching packages and re-sorts,
+    /// keeping the selection on the same package.
+    fn apply_access_times(&mut self, refreshed: Vec<Package>) {
+        let mut changed = false;
+        for fresh in refreshed {
+            if let Some(package) = self.items.iter_mut().find(|p| p.name == fresh.name) {
+                if package.last_accessed != fresh.last_accessed {
+                    package.last_accessed = fresh.last_accessed;
+                    package.time_source = fresh.time_source;
+                    package.last_accessed_path = fresh.last_accessed_path;
+                    changed = true;
+                }
+            }
+        }
+
+        if changed {
+            let selected = self.selected_index().map(|i| self.items[i].name.clone());
+            self.sort_packages();
+            if let Some(name) = selected {
+                self.select_by_name(&name);
+            }
+        }
+    }
+
+    /// Re-scans just the selected package in the background. The row is
+    /// updated in place once done; see `check_package_refresh`.
+    fn refresh_selected(&mut self) {
+        let index = match self.app_state {
+            AppState::PackageSelected(idx) => Some(idx),
+            AppState::Table => self.selected_index(),
+            _ => None,
+        };
+        if let Some(index) = index {
+            self.refresh_package_at(index);
+        }
+    }
+
+    fn refresh_package_at(&mut self, index: usize) {
+        if self.refresh_receiver.is_some() {
+            return;
+        }
+        let Some(mut package) = self.items.get(index).cloned() else {
+            return;
+        };
+        // A watch update in flight would copy its older access times back over the refresh
+        self.stop_watching();
+
+        let scanner = HomebrewScanner::new().with_options(self.config.scan_options());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let result = scanner
+                .refresh_package(&mut package)
+                .map(|()| package)
+                .map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.refresh_receiver = Some(receiver);
+    }
+
+    /// Merges a finished single-package refresh back in by name. The table
+    /// isn't re-sorted, so the selection and scroll position stay put.
+    fn check_package_refresh(&mut self) {
+        let Some(ref receiver) = self.refresh_receiver else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err("Refresh stopped unexpectedly".to_string())
+            }
+        };
+        self.refresh_receiver = None;
+
+        let fresh = match result {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                self.status_message = Some(format!("Refresh failed: {}", e));
+                self.status_success = false;
+                return;
+            }
+        };
+        // The package may have been deleted or rescanned away meanwhile
+        let Some(package) = self.items.iter_mut().find(|p| p.name == fresh.name) else {
+            return;
+        };
+        package.version = fresh.version;
+        package.last_accessed = fresh.last_accessed;
+        package.time_source = fresh.time_source;
+        package.last_accessed_path = fresh.last_accessed_path;
+        package.installed_at = fresh.installed_at;
+        package.size_bytes = fresh.size_bytes;
+        package.metadata = fresh.metadata;
+        package.paths = fresh.paths;
+
+        // Filters may now include or exclude it; rows otherwise keep their places
+        self.apply_filter();
+        self.status_message = Some(format!("Refreshed {}", fresh.name));
+        self.status_success = true;
+    }
+
+    fn has_scan_warnings(&self) -> bool {
+        self.get_scanning_state()
+            .is_some_and(|state| !state.package_warnings.is_empty())
+    }
+
+    fn show_results(&mut self) {
+        self.sort_packages();
+        self.app_state = AppState::Table;
+    }
+
+    /// Retries the last failed uninstall with `--force`.
+    fn retry_failed_delete(&mut self) {
+        if self.busy_with_background_delete() {
+            return;
+        }
+        let Some(ref name) = self.retry_target else {
+            return;
+        };
+
+        if let Some(index) = self.items.iter().position(|p| &p.name == name) {
+            self.execute_delete_with(
+                index,
+                UninstallOptions {
+                    force: true,
+                    ..Default::default()
+                },
+            );
+        } else {
+            self.retry_target = None;
+        }
+    }
+
+    fn sort_packages(&mut self) {
+        let followed = self.followed_selection();
+
+        self.preserving_marks(Self::sort_items);
+        self.reset_selection();
+        self.apply_filter();
+
+        if let Some(name) = followed {
+            self.select_by_name(&name);
+        }
+    }
+
+    /// The selected package's name, when the selection should survive a re-sort.
+    fn followed_selection(&self) -> Option<String> {
+        if !self.config.selection_follows_sort {
+            return None;
+        }
+        self.selected_index().map(|i| self.items[i].name.clone())
+    }
+
+    /// Selects `row`, or the last row if the list has become shorter.
+    fn select_row_near(&mut self, row: Option<usize>) {
+        let visible = self.visible_row_count();
+        let row = (visible > 0).then(|| row.unwrap_or(0).min(visible - 1));
+        self.state.select(row);
+        self.update_scroll_state();
+    }
+
+    /// Moves the selection to `name`'s row, if it is visible.
+    fn select_by_name(&mut self, name: &str) {
+        if let Some(row) = self
+            .filtered_indices
+            .iter()
+            .position(|&i| self.items[i].name == name)
+        {
+            self.state.select(Some(row));
+            self.update_scroll_state();
+        }
+    }
+
+    /// Runs `reorder` on `items` and re-points marks and batch statuses at the
+    /// same packages afterwards, since both are keyed by index.
+    fn preserving_marks(&mut self, reorder: impl FnOnce(&mut Self)) {
+        let marked: Vec<String> = self
+            .marked
+            .iter()
+            .filter_map(|&i| self.items.get(i).map(|p| p.name.clone()))
+            .collect();
+        let statuses: Vec<(String, BatchStatus)> = self
+            .batch_status
+            .drain()
+            .filter_map(|(i, status)| self.items.get(i).map(|p| (p.name.clone(), status)))
+            .collect();
+
+        reorder(self);
+
+        let position = |name: &str| self.items.iter().position(|p| p.name == name);
+        self.marked = marked.iter().filter_map(|name| position(name)).collect();
+        self.batch_status = statuses
+            .into_iter()
+            .filter_map(|(name, status)| position(&name).map(|i| (i, status)))
+            .collect();
+    }
+
+    /// Saves every scanned package, hidden or not, for diffing between
+    /// machines or reinstalling elsewhere.
+    fn export_results(&mut self, format: ExportFormat) {
+        match export::export(format, &self.items) {
+            Ok(path) => {
+                self.status_success = true;
+                self.status_message = Some(format!(
+                    "Exported {} packages to {}",
+                    self.items.len(),
+                    path.display()
+                ));
+            }
+            Err(e) => {
+                self.status_success = false;
+                self.status_message = Some(format!("Export failed: {}", e));
+            }
+        }
+    }
+
+    /// Copies the names of every visible row, in on-screen order.
+    fn copy_visible_names(&mut self) {
+        let names: Vec<&str> = self
+            .filtered_indices
+            .iter()
+            .map(|&i| self.items[i].name.as_str())
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+
+        match copy_to_clipboard(&(names.join("\n") + "\n")) {
+            Ok(()) => {
+                self.status_success = true;
+                self.status_message = Some(format!(
+                    "{} Copied {} package names to the clipboard",
+                    self.icon("📋", "[ok]"),
+                    names.len()
+                ));
+            }
+            Err(e) => {
+                self.status_success = false;
+                self.status_message = Some(e);
+            }
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(index) = self.selected_index() {
+            if !self.marked.remove(&index) && !self.refuse_pinned(index) {
+                self.marked.insert(index);
+            }
+            self.next_row();
+        }
+    }
+
+    fn prompt_select_by_age(&mut self) {
+        if !self.filtered_indices.is_empty() {
+            self.app_state = AppState::SelectByAge(String::new());
+        }
+    }
+
+    fn handle_age_input(&mut self, code: KeyCode) {
+        let AppState::SelectByAge(ref mut input) = self.app_state else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 5 => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                if let Ok(days) = input.parse() {
+                    self.mark_older_than(days);
+                    self.app_state = AppState::Table;
+                }
+            }
+            KeyCode::Esc => self.app_state = AppState::Table,
+            _ => {}
+        }
+    }
+
+    fn prompt_size_filter(&mut self) {
+        self.size_filter_error = None;
+        self.app_state = AppState::SizeFilter(String::new());
+    }
+
+    /// Edits the size range prompt. Enter on an empty prompt clears the filter.
+    fn handle_size_filter_input(&mut self, code: KeyCode) {
+        let AppState::SizeFilter(ref mut input) = self.app_state else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) if input.len() < 24 => {
+                input.push(c);
+                self.size_filter_error = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                self.size_filter_error = None;
+            }
+            KeyCode::Enter if input.trim().is_empty() => {
+                self.size_filter = None;
+                self.app_state = AppState::Table;
+                self.apply_filter();
+            }
+            KeyCode::Enter => match SizeRange::parse(input) {
+                Ok(range) => {
+                    self.size_filter = Some(range);
+                    self.app_state = AppState::Table;
+                    self.apply_filter();
+                }
+                Err(e) => self.size_filter_error = Some(e),
+            },
+            KeyCode::Esc => self.app_state = AppState::Table,
+            _ => {}
+        }
+    }
+
+    fn start_search(&mut self) {
+        self.searching = true;
+    }
+
+    /// Edits the name filter, narrowing the table as the query is typed.
+    /// Enter keeps the filter; Esc drops it.
+    fn handle_search_input(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) if self.filter.len() < 64 => {
+                self.filter.push(c);
+                self.apply_filter();
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.apply_filter();
+            }
+            KeyCode::Up => self.previous_row(),
+            KeyCode::Down => self.next_row(),
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => self.clear_search(),
+            _ => {}
+        }
+    }
+
+    fn clear_search(&mut self) {
+        self.searching = false;
+        self.filter.clear();
+        self.apply_filter();
+    }
+
+    /// Count and total size of the visible packages.
+    fn visible_totals(&self) -> (usize, u64) {
+        let total = self
+            .filtered_indices
+            .iter()
+            .filter_map(|&i| self.items[i].size_bytes)
+            .sum();
+        (self.filtered_indices.len(), total)
+    }
+
+    /// Visible packages idle for more than `days`, and how many of those are
+    /// skipped for being kept or pinned.
+    fn packages_older_than(&self, days: u64) -> (Vec<usize>, usize) {
+        let (kept, old): (Vec<usize>, Vec<usize>) = self
+            .filtered_indices
+            .iter()
+            .copied()
+            .filter(|&i| self.items[i].idle_longer_than(days))
+            .partition(|&i| self.is_kept(&self.items[i]) || self.is_pinned(&self.items[i]));
+        (old, kept.len())
+    }
+
+    /// Adds every visible package idle for more than `days` to the batch marks.
+    fn mark_older_than(&mut self, days: u64) {
+        let (matches, kept) = self.packages_older_than(days);
+        let count = matches.len();
+        self.marked.extend(matches);
+
+        self.status_success = true;
+        self.status_message = Some(format!(
+            "Marked {} package{} idle for more than {} day{} ({} marked in total){}",
+            count,
+            if count == 1 { "" } else { "s" },
+            days,
+            if days == 1 { "" } else { "s" },
+            self.marked.len(),
+            if kept > 0 {
+                format!(", skipped {} kept or pinned", kept)
+            } else {
+                String::new()
+            }
+        ));
+    }
+
+    fn confirm_batch_delete(&mut self) {
+        if !self.marked.is_empty() && !self.busy_with_background_delete() {
+            self.stop_watching();
+            self.large_batch_armed = false;
+            self.batch_force = false;
+            self.open_screen(AppState::ConfirmBatchDelete);
+        }
+    }
+
+    fn marked_total_size(&self) -> u64 {
+        self.marked
+            .iter()
+            .filter_map(|&i| self.items[i].size_bytes)
+            .sum()
+    }
+
+    /// Whether the marked batch crosses `large_batch_count` or `large_batch_size_mb`.
+    /// Dry runs never count as large, so they skip the second confirmation.
+    fn is_large_batch(&self) -> bool {
+        if self.dry_run {
+            return false;
+        }
+        let count = self.config.large_batch_count;
+        let size_mb = self.config.large_batch_size_mb;
+        (count > 0 && self.marked.len() >= count)
+            || (size_mb > 0 && self.marked_total_size() >= size_mb * 1024 * 1024)
+    }
+
+    /// Handles `y`/`Enter` on the batch confirmation. Large batches take a
+    /// second press so one stray key can't remove dozens of packages.
+    fn press_confirm_batch_delete(&mut self) {
+        if self.is_large_batch() && !self.large_batch_armed {
+            self.large_batch_armed = true;
+            return;
+        }
+        self.large_batch_armed = false;
+        self.execute_batch_delete();
+    }
+
+    /// Marked packages in table order.
+    fn marked_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self.marked.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    fn execute_batch_delete(&mut self) {
+        // Marks are refused for pinned packages; this catches any package
+        // pinned after it was marked
+        let pinned: Vec<usize> = self
+            .marked
+            .iter()
+            .copied()
+            .filter(|&i| self.is_pinned(&self.items[i]))
+            .collect();
+        for index in pinned {
+            self.marked.remove(&index);
+        }
+        let indices = self.marked_indices();
+        if indices.is_empty() {
+            self.app_state = AppState::Table;
+            return;
+        }
+        let packages: Vec<(usize, Package)> = indices
+            .iter()
+            .map(|&i| (i, self.items[i].clone()))
+            .collect();
+
+        self.batch_status = indices.iter().map(|&i| (i, BatchStatus::Pending)).collect();
+        self.clear_output();
+        self.retry_target = None;
+        let options = UninstallOptions {
+            force: self.batch_force,
+            dry_run: self.dry_run,
+            ..Default::default()
+        };
+
+        let (output_sender, output_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+        self.delete_output_receiver = Some(output_receiver);
+        self.batch_event_receiver = Some(event_receiver);
+        self.app_state = AppState::BatchDeleting;
+
+        // Uninstall one package at a time; brew holds a global lock anyway
+        thread::spawn(move || {
+            let scanner = HomebrewScanner::new();
+            for (index, package) in packages {
+                let _ = event_sender.send(BatchEvent::Started(index));
+                let result =
+                    scanner.delete_package_with_output(&package, &options, output_sender.clone());
+                let _ = event_sender.send(BatchEvent::Finished(index, result));
+            }
+        });
+    }
+
+    fn check_batch_progress(&mut self) {
+        self.drain_delete_output();
+
+        let Some(ref receiver) = self.batch_event_receiver else {
+            return;
+        };
+
+        let mut finished = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(BatchEvent::Started(index)) => {
+                    self.batch_status.insert(index, BatchStatus::InProgress);
+                }
+                Ok(BatchEvent::Finished(index, result)) => {
+                    let status = match result {
+                        Ok(()) => BatchStatus::Done,
+                        Err(e) => BatchStatus::Failed(e),
+                    };
+                    self.batch_status.insert(index, status);
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        if finished {
+            self.finish_batch();
+        }
+    }
+
+    /// Drops the uninstalled packages; failed ones stay listed with their error.
+    fn finish_batch(&mut self) {
+        self.batch_event_receiver = None;
+        self.delete_output_receiver = None;
+
+        if self.dry_run {
+            // Nothing was removed; the marks stay for a real run
+            self.batch_status.clear();
+            self.status_success = true;
+            self.status_message = Some(format!(
+                "Dry run: {} packages were not removed (N to turn dry run off)",
+                self.marked.len()
+            ));
+            return;
+        }
+
+        let done: HashSet<String> = self
+            .batch_status
+            .iter()
+            .filter(|(_, status)| **status == BatchStatus::Done)
+            .filter_map(|(&i, _)| self.items.get(i).map(|p| p.name.clone()))
+            .collect();
+        let failed = self.batch_status.len() - done.len();
+        let (freed, known) = self
+            .items
+            .iter()
+            .filter(|p| done.contains(&p.name))
+            .filter_map(|p| p.size_bytes)
+            .fold((0, 0), |(total, known), size| (total + size, known + 1));
+
+        self.marked.clear();
+        self.preserving_marks(|app| app.items.retain(|p| !done.contains(&p.name)));
+        self.batch_status
+            .retain(|_, status| matches!(status, BatchStatus::Failed(_)));
+
+        self.longest_item_lens = constraint_len_calculator(&self.items);
+        self.apply_filter();
+
+        self.status_success = failed == 0;
+        self.status_message = Some(format!(
+            "Batch uninstall: {} removed, {} failed{}",
+            done.len(),
+            failed,
+            if known > 0 {
+                format!(", freed {}", format_size(freed))
+            } else {
+                String::new()
+            }
+        ));
+    }
+
+    /// Marks exactly the packages that failed in the last batch, for a retry with `D`.
+    fn requeue_failed_batch(&mut self) {
+        let failed: HashSet<usize> = self
+            .batch_status
+            .iter()
+            .filter(|(_, status)| matches!(status, BatchStatus::Failed(_)))
+            .map(|(&i, _)| i)
+            .collect();
+
+        if failed.is_empty() {
+            self.status_success = false;
+            self.status_message = Some("No failed batch uninstalls to requeue".to_string());
+            return;
+        }
+
+        self.status_success = true;
+        self.status_message = Some(format!(
+            "Marked {} failed package{} again. Press D to retry (f there adds --force)",
+            failed.len(),
+            if failed.len() == 1 { "" } else { "s" }
+        ));
+        self.marked = failed;
+    }
+
+    fn batch_running(&self) -> bool {
+        self.batch_event_receiver.is_some()
+    }
+
+    fn sort_items(&mut self) {
+        let (mode, reversed) = (self.sort_mode, self.sort_reversed);
+        self.items.sort_by(|a, b| {
+            let ordering = mode.compare(a, b);
+            if reversed {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// `s`: moves to the next sort mode, re-sorting the table if it's showing.
+    fn cycle_sort_mode(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        if matches!(self.app_state, AppState::Table) {
+            self.sort_packages();
+        }
+    }
+
+    /// `S`: flips the current sort order.
+    fn toggle_sort_direction(&mut self) {
+        self.sort_reversed = !self.sort_reversed;
+        if matches!(self.app_state, AppState::Table) {
+            self.sort_packages();
+        }
+    }
+
+    /// Arrow for the sorted column's header: up when values grow down the table.
+    fn sort_arrow(&self) -> &'static str {
+        if self.sort_mode.ascending() != self.sort_reversed {
+            self.icon("▲", "^")
+        } else {
+            self.icon("▼", "v")
+        }
+    }
+
+    fn reset_selection(&mut self) {
+        // Reset selection to top after sorting
+        if !self.items.is_empty() {
+            self.state.select(Some(0));
+            self.scroll_state = self.scroll_state.position(0);
+        }
+    }
+
+    /// Picks the emoji or its ASCII stand-in depending on `ascii_mode`.
+    fn icon(&self, emoji: &'static str, ascii: &'static str) -> &'static str {
+        if self.config.ascii_mode {
+            ascii
+        } else {
+            emoji
+        }
+    }
+
+    fn get_scanning_state(&self) -> Option<ScanningState> {
+        self.scanner.as_ref().map(|s| s.get_state())
+    }
+
+    pub fn next_row(&mut self) {
+        if !matches!(self.app_state, AppState::Table) || self.visible_row_count() == 0 {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.visible_row_count() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * self.item_height());
+        self.retry_target = None;
+    }
+
+    pub fn previous_row(&mut self) {
+        if !matches!(self.app_state, AppState::Table) || self.visible_row_count() == 0 {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.visible_row_count() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * self.item_height());
+        self.retry_target = None;
+    }
+
+    pub fn first_row(&mut self) {
+        self.jump_to_row(0);
+    }
+
+    pub fn last_row(&mut self) {
+        self.jump_to_row(usize::MAX);
+    }
+
+    pub fn page_down(&mut self) {
+        let row = self
+            .state
+            .selected()
+            .map_or(0, |row| row + self.rows_per_page());
+        self.jump_to_row(row);
+    }
+
+    pub fn page_up(&mut self) {
+        let row = self
+            .state
+            .selected()
+            .map_or(0, |row| row.saturating_sub(self.rows_per_page()));
+        self.jump_to_row(row);
+    }
+
+    /// Selects table row `row`, stopping at the last one rather than wrapping.
+    fn jump_to_row(&mut self, row: usize) {
+        if !matches!(self.app_state, AppState::Table) || self.visible_row_count() == 0 {
+            return;
+        }
+
+        let row = row.min(self.visible_row_count() - 1);
+        self.state.select(Some(row));
+        self.scroll_state = self.scroll_state.position(row * self.item_height());
+        self.retry_target = None;
+    }
+
+    pub fn next_column(&mut self) {
+        if matches!(self.app_state, AppState::Table) {
+            self.state.select_next_column();
+        }
+    }
+
+    pub fn previous_column(&mut self) {
+        if matches!(self.app_state, AppState::Table) {
+            self.state.select_previous_column();
+        }
+    }
+
+    pub fn next_color(&mut self) {
+        self.select_color((self.color_index + 1) % PALETTES.len());
+    }
+
+    pub fn previous_color(&mut self) {
+        let count = PALETTES.len();
+        self.select_color((self.color_index + count - 1) % count);
+    }
+
+    pub fn select_color(&mut self, index: usize) {
+        if index < PALETTES.len() {
+            self.color_index = index;
+            self.palette_switched = Some(Instant::now());
+            self.config.color_index = index;
+            if let Err(e) = self.config.save_config() {
+                self.status_message = Some(e);
+            }
+        }
+    }
+
+    pub fn set_colors(&mut self) {
+        self.colors = TableColors::new(&PALETTES[self.color_index]);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if let Some(ref scanner) = self.scanner {
+            scanner.toggle_pause();
+        }
+    }
+
+    /// Space on the scanning screen: retries a failed scan with a fresh
+    /// scanner, otherwise pauses or resumes the running one.
+    fn retry_or_toggle_pause(&mut self) {
+        let failed = self
+            .get_scanning_state()
+            .is_some_and(|state| state.error_message.is_some());
+        if failed {
+            self.scanner = None;
+            self.scan_handle = None;
+            self.start_scanning();
+        } else {
+            self.toggle_pause();
+        }
+    }
+
+    fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let size = terminal.size()?;
+        self.terminal_size = (size.width, size.height);
+
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Update scanning progress
+            if matches!(self.app_state, AppState::Scanning) {
+                self.update_scan();
+            }
+
+            if matches!(self.app_state, AppState::Deleting(_)) {
+                self.check_delete_progress();
+            }
+
+            if self.background_delete.is_some() && matches!(self.app_state, AppState::Table) {
+                self.check_background_delete();
+            }
+
+            if matches!(self.app_state, AppState::BatchDeleting) {
+                self.check_batch_progress();
+            }
+
+            if matches!(self.app_state, AppState::PackageSelected(_)) {
+                self.check_reported_size();
+                self.check_deps();
+                self.check_size_breakdown();
+            }
+
+            if matches!(self.app_state, AppState::ConfirmDelete(_)) {
+                self.check_confirm_hold();
+                self.check_dependents();
+            }
+
+            if self.cache_size_receiver.is_some() {
+                self.check_cache_size();
+            }
+
+            if self.refresh_receiver.is_some() {
+                self.check_package_refresh();
+            }
+
+            if matches!(self.app_state, AppState::ConfirmMaintenance(_)) {
+                self.check_maintenance_preview();
+            }
+
+            if matches!(self.app_state, AppState::RunningMaintenance(_)) {
+                self.check_maintenance_progress();
+            }
+
+            if matches!(self.app_state, AppState::Table) {
+                self.check_watch();
+                // Nothing to go back to from the table, however it was reached
+                self.back_stack.clear();
+            }
+
+            // Handle events with timeout for responsive UI
+            if event::poll(Duration::from_millis(100))? {
+                let event = event::read()?;
+                if let Event::Resize(width, height) = event {
+                    self.handle_resize(width, height);
+                }
+                if let Event::Key(key) = event {
+                    if key.kind == KeyEventKind::Press {
+                        if self.show_help {
+                            self.handle_help_key(key.code);
+                            continue;
+                        }
+                        if matches!(self.app_state, AppState::SelectByAge(_)) {
+                            self.handle_age_input(key.code);
+                            continue;
+                        }
+                        if matches!(self.app_state, AppState::SizeFilter(_)) {
+                            self.handle_size_filter_input(key.code);
+                            continue;
+                        }
+                        if self.searching && matches!(self.app_state, AppState::Table) {
+                            self.handle_search_input(key.code);
+                            continue;
+                        }
+                        if let AppState::ConfirmQuit(ref interrupted) = self.app_state {
+                            match key.code {
+                                KeyCode::Char('y') | KeyCode::Enter => {
+                                    let _ = self.save_cache();
+                                    return Ok(());
+                                }
+                                KeyCode::Char('n') | KeyCode::Char(' ') | KeyCode::Esc => {
+                                    self.app_state = interrupted.as_ref().clone();
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        if matches!(self.app_state, AppState::NoBrew) {
+                            match key.code {
+                                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                                KeyCode::Char('r') => self.recheck_brew(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        let shift_pressed = key.modifiers.contains(KeyModifiers::SHIFT);
+                        match key.code {
+                            KeyCode::Esc
+                                if matches!(self.app_state, AppState::Table)
+                                    && !self.filter.is_empty() =>
+                            {
+                                self.clear_search()
+                            }
+                            KeyCode::Esc if !self.at_top_level() => self.go_back(),
+                            KeyCode::Char('q') | KeyCode::Esc
+                                if self.running_operation(&self.app_state).is_some() =>
+                            {
+                                let interrupted =
+                                    std::mem::replace(&mut self.app_state, AppState::Table);
+                                self.app_state = AppState::ConfirmQuit(Box::new(interrupted));
+                            }
+                            KeyCode::Char('q') | KeyCode::Esc => {
+                                // Nowhere left to report a failure; the next scan rewrites it
+                                let _ = self.save_cache();
+                                return Ok(());
+                            }
+                            KeyCode::Char('?') => self.show_help = true,
+                            KeyCode::Char('/') if matches!(self.app_state, AppState::Table) => {
+                                self.start_search()
+                            }
+                            KeyCode::Char(' ') => match self.app_state {
+                                AppState::Table => self.start_scanning(),
+                                AppState::Scanning => self.retry_or_toggle_pause(),
+                                AppState::ScanComplete => self.show_results(),
+                                AppState::PackageSelected(_) => self.app_state = AppState::Table,
+                                AppState::ConfirmDelete(_) => self.app_state = AppState::Table,
+                                AppState::Deleting(_) if !self.delete_running() => {
+                                    self.app_state = AppState::Table
+                                }
+                                AppState::Deleting(_) => self.cancel_delete(),
+                                AppState::ConfirmBatchDelete => self.app_state = AppState::Table,
+                                AppState::BatchDeleting => {
+                                    if !self.batch_running() {
+                                        self.app_state = AppState::Table;
+                                    }
+                                }
+                                AppState::SelectByAge(_) | AppState::SizeFilter(_) => {}
+                                AppState::MaintenanceMenu | AppState::ConfirmMaintenance(_) => {
+                                    self.app_state = AppState::Table
+                                }
+                                AppState::RunningMaintenance(_) => self.cancel_maintenance(),
+                                AppState::History => self.app_state = AppState::Table,
+                                AppState::ScanWarnings => self.app_state = AppState::ScanComplete,
+                                AppState::BrokenLinks => self.app_state = AppState::MaintenanceMenu,
+                                AppState::NoBrew | AppState::ConfirmQuit(_) => {}
+                                AppState::TopSizes(_) => self.go_back(),
+                            },
+                            KeyCode::Enter => match self.app_state {
+                                AppState::Table => self.activate_selected_row(),
+                                AppState::TopSizes(row) => self.jump_to_top_size(row),
+                                AppState::ScanComplete => self.show_results(),
+                                AppState::PackageSelected(_) => self.app_state = AppState::Table,
+                                AppState::ConfirmDelete(idx) => self.press_confirm_delete(idx),
+                                AppState::ConfirmBatchDelete => self.press_confirm_batch_delete(),
+                                AppState::ConfirmMaintenance(_) => self.execute_maintenance(),
+                                AppState::BatchDeleting if !self.batch_running() => {
+                                    self.app_state = AppState::Table
+                                }
+                                AppState::Deleting(_) if !self.delete_running() => {
+                                    self.app_state = AppState::Table
+                                }
+                                _ => {}
+                            },
+                            KeyCode::Char('d') | KeyCode::Delete => match self.app_state {
+                                AppState::Table => self.delete_selected_package(),
+                                AppState::PackageSelected(idx) => self.confirm_delete(idx),
+                                _ => {}
+                            },
+                            KeyCode::Char('s') => {
+                                if matches!(
+                                    self.app_state,
+                                    AppState::ScanComplete | AppState::Table
+                                ) {
+                                    self.cycle_sort_mode();
+                                }
+                            }
+                            KeyCode::Char('S') => {
+                                if matches!(
+                                    self.app_state,
+                                    AppState::ScanComplete | AppState::Table
+                                ) {
+                                    self.toggle_sort_direction();
+                                }
+                            }
+                            KeyCode::Char('c') => match self.app_state {
+                                AppState::Table => self.toggle_density(),
+                                AppState::Deleting(_) if self.delete_running() => {
+                                    self.stop_watching_delete()
+                                }
+                                AppState::MaintenanceMenu => {
+                                    self.confirm_maintenance(MaintenanceAction::Cleanup)
+                                }
+                                AppState::BrokenLinks if !self.broken_links.is_empty() => {
+                                    self.confirm_maintenance(MaintenanceAction::PrunePrefix)
+                                }
+                                _ => {}
+                            },
+                            KeyCode::Char('p') => match self.app_state {
+                                AppState::MaintenanceMenu => {
+                                    self.confirm_maintenance(MaintenanceAction::PruneCache)
+                                }
+                                AppState::Table | AppState::PackageSelected(_) => self.toggle_pin(),
+                                _ => {}
+                            },
+                            KeyCode::Char('Z') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.prompt_size_filter();
+                                }
+                            }
+                            KeyCode::Char('R') => {
+                                if matches!(
+                                    self.app_state,
+                                    AppState::Table | AppState::PackageSelected(_)
+                                ) {
+                                    self.refresh_selected();
+                                }
+                            }
+                            KeyCode::Char('A') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.requeue_failed_batch();
+                                }
+                            }
+                            KeyCode::Char('f') if matches!(self.app_state, AppState::Table) => {
+                                self.cycle_idle_filter()
+                            }
+                            KeyCode::Char('f')
+                                if matches!(self.app_state, AppState::ConfirmBatchDelete) =>
+                            {
+                                self.batch_force = !self.batch_force;
+                                self.large_batch_armed = false;
+                            }
+                            KeyCode::Char('o') => match self.app_state {
+                                AppState::Table => self.preview_autoremove(),
+                                AppState::PackageSelected(idx) => self.open_homepage(idx),
+                                _ => {}
+                            },
+                            KeyCode::Char('T') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.history = history::load();
+                                    self.open_screen(AppState::History);
+                                }
+                            }
+                            KeyCode::Char('M') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.open_maintenance_menu();
+                                }
+                            }
+                            KeyCode::Char('F') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.retry_failed_delete();
+                                }
+                            }
+                            KeyCode::Char('z') => match self.app_state {
+                                AppState::Table => self.toggle_group_small(),
+                                AppState::ConfirmDelete(idx) => self.toggle_delete_zap(idx),
+                                _ => {}
+                            },
+                            KeyCode::Char('x') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_reviewed();
+                                }
+                            }
+                            KeyCode::Char('U') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_only_unused();
+                                }
+                            }
+                            KeyCode::Char('O') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_only_outdated();
+                                }
+                            }
+                            KeyCode::Char('u') => {
+                                if let AppState::PackageSelected(idx) = self.app_state {
+                                    self.upgrade_package(idx);
+                                }
+                            }
+                            KeyCode::Char('N') if matches!(self.app_state, AppState::Table) => {
+                                self.toggle_dry_run()
+                            }
+                            KeyCode::Char('b') => match self.app_state {
+                                AppState::Table => self.short_paths = !self.short_paths,
+                                AppState::MaintenanceMenu => self.confirm_maintenance(
+                                    MaintenanceAction::RestoreBrewfile(self.config.brewfile_path()),
+                                ),
+                                _ => {}
+                            },
+                            KeyCode::Char('I') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.show_idle_days = !self.show_idle_days;
+                                }
+                            }
+                            KeyCode::Char('K') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_keep();
+                                }
+                            }
+                            KeyCode::Char('X') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_hide_reviewed();
+                                }
+                            }
+                            KeyCode::Char('v') => {
+                                if let AppState::PackageSelected(idx) = self.app_state {
+                                    self.verify_package(idx);
+                                }
+                            }
+                            KeyCode::Char('t') => match self.app_state {
+                                AppState::PackageSelected(_) => self.show_dates = !self.show_dates,
+                                AppState::Table | AppState::ScanComplete => {
+                                    self.open_screen(AppState::TopSizes(0))
+                                }
+                                AppState::TopSizes(_) => self.go_back(),
+                                _ => {}
+                            },
+                            KeyCode::Char('i') => {
+                                if let AppState::PackageSelected(idx) = self.app_state {
+                                    self.toggle_full_details(idx);
+                                }
+                            }
+                            KeyCode::Char('r') => match self.app_state {
+                                AppState::Table => self.start_scanning(),
+                                AppState::PackageSelected(idx) => self.reveal_package(idx),
+                                _ => {}
+                            },
+                            KeyCode::Char('y') => match self.app_state {
+                                AppState::ConfirmDelete(idx) => self.press_confirm_delete(idx),
+                                AppState::ConfirmBatchDelete => self.press_confirm_batch_delete(),
+                                AppState::ConfirmMaintenance(_) => self.execute_maintenance(),
+                                _ => {}
+                            },
+                            KeyCode::Char('n') => match self.app_state {
+                                AppState::ConfirmDelete(_)
+                                | AppState::ConfirmBatchDelete
+                                | AppState::MaintenanceMenu
+                                | AppState::ConfirmMaintenance(_)
+                                | AppState::History => self.app_state = AppState::Table,
+                                AppState::BrokenLinks => self.app_state = AppState::MaintenanceMenu,
+                                AppState::ScanWarnings => self.app_state = AppState::ScanComplete,
+                                _ => {}
+                            },
+                            KeyCode::Char('l')
+                                if matches!(self.app_state, AppState::MaintenanceMenu) =>
+                            {
+                                self.open_screen(AppState::BrokenLinks);
+                            }
+                            KeyCode::Char('w') => match self.app_state {
+                                AppState::Table => self.toggle_watching(),
+                                AppState::ScanComplete if self.has_scan_warnings() => {
+                                    self.open_screen(AppState::ScanWarnings)
+                                }
+                                _ => {}
+                            },
+                            KeyCode::Char('Y') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.copy_visible_names();
+                                }
+                            }
+                            KeyCode::Char('e')
+                                if matches!(self.app_state, AppState::Table)
+                                    && self.has_scanned =>
+                            {
+                                self.export_results(ExportFormat::Json)
+                            }
+                            KeyCode::Char('E')
+                                if matches!(self.app_state, AppState::Table)
+                                    && self.has_scanned =>
+                            {
+                                self.export_results(ExportFormat::Csv)
+                            }
+                            KeyCode::Char('B')
+                                if matches!(self.app_state, AppState::Table)
+                                    && self.has_scanned =>
+                            {
+                                self.export_results(ExportFormat::Brewfile)
+                            }
+                            KeyCode::Char('a') => match self.app_state {
+                                AppState::Table => self.prompt_select_by_age(),
+                                AppState::MaintenanceMenu => {
+                                    self.confirm_maintenance(MaintenanceAction::Autoremove)
+                                }
+                                _ => {}
+                            },
+                            KeyCode::Char('m') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.toggle_mark();
+                                }
+                            }
+                            KeyCode::Char('D') => {
+                                if matches!(self.app_state, AppState::Table) {
+                                    self.confirm_batch_delete();
+                                }
+                            }
+                            KeyCode::Char('j') | KeyCode::Down
+                                if matches!(self.app_state, AppState::TopSizes(_)) =>
+                            {
+                                self.move_top_sizes(1)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up
+                                if matches!(self.app_state, AppState::TopSizes(_)) =>
+                            {
+                                self.move_top_sizes(-1)
+                            }
+                            KeyCode::Char('j') | KeyCode::Down if self.showing_output() => {
+                                self.scroll_output(1)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up if self.showing_output() => {
+                                self.scroll_output(-1)
+                            }
+                            KeyCode::PageDown if self.showing_output() => {
+                                self.scroll_output(self.output_viewport_height() as isize)
+                            }
+                            KeyCode::PageUp if self.showing_output() => {
+                                self.scroll_output(-(self.output_viewport_height() as isize))
+                            }
+                            KeyCode::Char('j') | KeyCode::Down
+                                if matches!(self.app_state, AppState::PackageSelected(_)) =>
+                            {
+                                self.scroll_deps(1)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up
+                                if matches!(self.app_state, AppState::PackageSelected(_)) =>
+                            {
+                                self.scroll_deps(-1)
+                            }
+                            KeyCode::Char('j') | KeyCode::Down => self.next_row(),
+                            KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
+                            KeyCode::Char('g') => self.first_row(),
+                            KeyCode::Char('G') => self.last_row(),
+                            KeyCode::PageDown => self.page_down(),
+                            KeyCode::PageUp => self.page_up(),
+                            KeyCode::Char('l') | KeyCode::Right if shift_pressed => {
+                                self.next_color()
+                            }
+                            KeyCode::Char('h') | KeyCode::Left if shift_pressed => {
+                                self.previous_color();
+                            }
+                            KeyCode::Char(digit @ '1'..='4')
+                                if matches!(self.app_state, AppState::Table) =>
+                            {
+                                self.select_color(digit as usize - '1' as usize);
+                            }
+                            KeyCode::Char('l') | KeyCode::Right => self.next_column(),
+                            KeyCode::Char('h') | KeyCode::Left => self.previous_column(),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        self.set_colors();
+
+        let screen = match self.app_state {
+            AppState::ConfirmQuit(ref interrupted) => interrupted.as_ref().clone(),
+            ref state => state.clone(),
+        };
+        match screen {
+            AppState::Scanning => self.render_scanning_ui(frame),
+            AppState::ScanComplete => self.render_scan_complete_ui(frame),
+            AppState::PackageSelected(idx) => self.render_package_details(frame, idx),
+            AppState::ConfirmDelete(idx) => self.render_confirm_delete(frame, idx),
+            AppState::Deleting(idx) => self.render_deleting(frame, idx),
+            AppState::ConfirmBatchDelete => self.render_confirm_batch_delete(frame),
+            AppState::BatchDeleting => self.render_batch_deleting(frame),
+            AppState::SelectByAge(ref input) => self.render_select_by_age(frame, input),
+            AppState::SizeFilter(ref input) => self.render_size_filter(frame, input),
+            AppState::MaintenanceMenu => self.render_maintenance_menu(frame),
+            AppState::BrokenLinks => self.render_broken_links(frame),
+            AppState::History => self.render_history(frame),
+            AppState::ScanWarnings => self.render_scan_warnings(frame),
+            AppState::NoBrew => self.render_no_brew(frame),
+            AppState::TopSizes(row) => self.render_top_sizes(frame, row),
+            AppState::ConfirmMaintenance(ref action) => {
+                self.render_confirm_maintenance(frame, action)
+            }
+            AppState::RunningMaintenance(ref action) => {
+                self.render_running_maintenance(frame, action)
+            }
+            AppState::Table => {
+                let vertical =
+                    &Layout::vertical([Constraint::Min(5), Constraint::Length(FOOTER_HEIGHT)]);
+                let rects = vertical.split(frame.area());
+
+                self.render_table(frame, rects[0]);
+                if !self.items.is_empty() {
+                    self.render_scrollbar(frame, rects[0]);
+                }
+                self.render_footer(frame, rects[1]);
+            }
+            // Never nested: the prompt only opens over a running operation
+            AppState::ConfirmQuit(_) => {}
+        }
+
+        if self.show_help {
+            self.render_help(frame);
+        }
+        if let AppState::ConfirmQuit(ref interrupted) = self.app_state {
+            self.render_confirm_quit(frame, interrupted);
+        }
+    }
+
+    /// What quitting from `screen` would cut short, if anything.
+    fn running_operation(&self, screen: &AppState) -> Option<String> {
+        match *screen {
+            AppState::Scanning
+                if self
+                    .get_scanning_state()
+                    .is_some_and(|state| !state.scan_complete) =>
+            {
+                Some("A scan is running".to_string())
+            }
+            AppState::Deleting(_) if self.delete_running() => {
+                Some("An uninstall is running".to_string())
+            }
+            AppState::BatchDeleting if self.batch_running() => {
+                Some("A batch uninstall is running".to_string())
+            }
+            AppState::RunningMaintenance(ref action) => {
+                Some(format!("{} is running", action.label()))
+            }
+            _ => self
+                .background_delete
+                .as_ref()
+                .map(|name| format!("'{}' is still uninstalling in the background", name)),
+        }
+    }
+
+    /// A small red prompt over the screen quitting would interrupt.
+    fn render_confirm_quit(&self, frame: &mut Frame, interrupted: &AppState) {
+        let area = frame.area();
+        frame
+            .buffer_mut()
+            .set_style(area, Style::default().add_modifier(Modifier::DIM));
+
+        // The operation may have finished since the prompt opened
+        let operation = self
+            .running_operation(interrupted)
+            .unwrap_or_else(|| "Nothing is running any more".to_string());
+        let lines = vec![
+            Line::from(format!("{} — quit anyway?", operation)),
+            Line::from(""),
+            Line::styled("[y/Enter] Quit  [n/Esc] Keep going", Color::Gray),
+        ];
+        let width = (lines[0].width() as u16 + 6).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        let block = Block::default()
+            .title(format!("{} Quit", self.icon("⚠️ ", "[!]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(self.colors.buffer_bg));
+        let prompt = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Red))
+            .block(block);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(prompt, popup);
+    }
+
+    /// Where the help overlay's columns go on a screen of `area`.
+    fn help_area(area: Rect) -> Rect {
+        // Inside the popup's one-cell margin and its borders
+        area.inner(Margin::new(3, 2))
+    }
+
+    /// Columns of help that fit side by side in `area`.
+    fn help_columns_per_page(area: Rect) -> usize {
+        (area.width / HELP_COLUMN_WIDTH).max(1) as usize
+    }
+
+    /// `HELP_SECTIONS` and the table marks, as columns of at most `height`
+    /// lines. A section longer than the space left carries on in the next
+    /// column under a "(cont.)" title.
+    fn help_columns(&self, height: usize) -> Vec<Vec<Line<'static>>> {
+        const KEY_WIDTH: usize = 12;
+
+        // Table marks follow ascii_mode, so they are listed here
+        let marks = [
+            (
+                format!("{} {}", self.icon("★", "@"), self.icon("●", "*")),
+                "Favorite, marked for batch",
+            ),
+            (
+                self.icon("♻", "o").to_string(),
+                "Autoremove would remove it",
+            ),
+            (self.icon("∅", "0").to_string(), "Unused since install"),
+            (
+                format!("{} {}", self.icon("⚓", "k"), self.icon("✓", "+")),
+                "Kept, reviewed",
+            ),
+            (
+                self.icon("≈", "~").to_string(),
+                "Modification time, not access",
+            ),
+        ];
+        let sections = HELP_SECTIONS
+            .iter()
+            .map(|(title, keys)| {
+                let keys: Vec<(String, &str)> = keys
+                    .iter()
+                    .map(|(key, action)| (key.to_string(), *action))
+                    .collect();
+                (*title, keys)
+            })
+            .chain([("Marks", marks.to_vec())]);
+
+        let height = height.max(2);
+        let title_style = Style::default()
+            .fg(self.colors.header_fg)
+            .add_modifier(Modifier::BOLD);
+        let mut columns: Vec<Vec<Line>> = vec![Vec::new()];
+        for (title, keys) in sections {
+            // A title needs at least one key under it, plus a gap after a section
+            let column = columns.last_mut().unwrap();
+            if !column.is_empty() && column.len() + 3 > height {
+                columns.push(Vec::new());
+            }
+            let column = columns.last_mut().unwrap();
+            if !column.is_empty() {
+                column.push(Line::default());
+            }
+            column.push(Line::styled(title.to_string(), title_style));
+
+            for (key, action) in keys {
+                if columns.last().unwrap().len() == height {
+                    columns.push(vec![Line::styled(
+                        format!("{} (cont.)", title),
+                        title_style,
+                    )]);
+                }
+                columns.last_mut().unwrap().push(Line::from(vec![
+                    Span::styled(
+                        // Padded by display width, as some marks are double-width
+                        format!(
+                            " {}{}",
+                            key,
+                            " ".repeat(KEY_WIDTH.saturating_sub(key.width()))
+                        ),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(action, Style::default().fg(self.colors.row_fg)),
+                ]));
+            }
+        }
+        columns
+    }
+
+    /// Pages of help on a screen of `area`.
+    fn help_page_count(&self, area: Rect) -> usize {
+        let inner = Self::help_area(area);
+        self.help_columns(inner.height as usize)
+            .len()
+            .div_ceil(Self::help_columns_per_page(inner))
+    }
+
+    /// A key while help is open: arrows and PgUp/PgDn turn the page, anything
+    /// else closes it.
+    fn handle_help_key(&mut self, code: KeyCode) {
+        let (width, height) = self.terminal_size;
+        let last = self
+            .help_page_count(Rect::new(0, 0, width, height))
+            .saturating_sub(1);
+        match code {
+            KeyCode::PageDown | KeyCode::Down | KeyCode::Right => {
+                self.help_page = (self.help_page + 1).min(last)
+            }
+            KeyCode::PageUp | KeyCode::Up | KeyCode::Left => {
+                self.help_page = self.help_page.saturating_sub(1)
+            }
+            _ => {
+                self.show_help = false;
+                self.help_page = 0;
+            }
+        }
+    }
+
+    /// Draws a page of `HELP_SECTIONS` in as many columns as fit, over the
+    /// dimmed screen.
+    fn render_help(&self, frame: &mut Frame) {
+        let area = frame.area();
+        frame
+            .buffer_mut()
+            .set_style(area, Style::default().add_modifier(Modifier::DIM));
+
+        let inner = Self::help_area(area);
+        let per_page = Self::help_columns_per_page(inner);
+        let columns = self.help_columns(inner.height as usize);
+        let pages = columns.len().div_ceil(per_page);
+        let page = self.help_page.min(pages.saturating_sub(1));
+
+        let hint = if pages > 1 {
+            format!(
+                " Page {}/{}: {} ↑/↓ to turn, any other key closes ",
+                page + 1,
+                pages,
+                if page + 1 < pages { "more…" } else { "end." }
+            )
+        } else {
+            " Press any key to close ".to_string()
+        };
+        let popup = area.inner(Margin::new(2, 1));
+        let block = Block::default()
+            .title(format!("{} Keys", self.icon("⌨️ ", "[?]")))
+            .title_bottom(Line::from(hint).centered())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+
+        let areas = Layout::horizontal(vec![Constraint::Fill(1); per_page]).split(inner);
+        let shown = columns.into_iter().skip(page * per_page).take(per_page);
+        for (column, area) in shown.zip(areas.iter()) {
+            frame.render_widget(Paragraph::new(column), *area);
+        }
+    }
+    fn render_scanning_ui(&self, frame: &mut Frame) {
+        let scanning_state = self.get_scanning_state().unwrap_or_else(ScanningState::new);
+
+        let scanning_block = Block::default()
+            .title(format!(
+                "{} Homebrew Package Scanner",
+                self.icon("🔍", "[scan]")
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(1), // Status
+                Constraint::Length(1), // Empty space
+                Constraint::Length(3), // Progress bar
+                Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Packages found
+                Constraint::Length(1), // Current scanning
+                Constraint::Length(1), // Elapsed time
+                Constraint::Length(1), // Error message (if any)
+                Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Controls
+            ])
+            .split(scanning_block.inner(frame.area()));
+
+        frame.render_widget(scanning_block, frame.area());
+
+        // Status text
+        let status_text = if let Some(ref error) = scanning_state.error_message {
+            format!("Error: {}", error)
+        } else if scanning_state.is_paused {
+            "Status: Scanning paused...".to_string()
+        } else {
+            "Status: Scanning installed packages...".to_string()
+        };
+
+        let status_color = if scanning_state.error_message.is_some() {
+            Color::Red
+        } else {
+            self.colors.row_fg
+        };
+
+        let status = Paragraph::new(status_text).style(Style::default().fg(status_color));
+        frame.render_widget(status, chunks[0]);
+
+        // Progress bar
+        let progress = Gauge::default()
+            .block(Block::default().title("Progress").borders(Borders::ALL))
+            .gauge_style(Style::default().fg(self.colors.footer_border_color))
+            .percent(scanning_state.progress_percentage())
+            .label(format!(
+                "{}% ({}/{})",
+                scanning_state.progress_percentage(),
+                scanning_state.packages_scanned,
+                scanning_state.total_packages
+            ));
+        frame.render_widget(progress, chunks[2]);
+
+        // Package count
+        let found = Paragraph::new(format!(
+            "{} Packages Found: {}",
+            self.icon("📦", "[pkg]"),
+            scanning_state.packages_found
+        ))
+        .style(Style::default().fg(Color::Green));
+        frame.render_widget(found, chunks[4]);
+
+        // Current scanning
+        let current = Paragraph::new(format!(
+            "{} Current: {}",
+            self.icon("📁", "[dir]"),
+            scanning_state.current_path
+        ))
+        .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(current, chunks[5]);
+
+        // Elapsed time
+        let elapsed = Paragraph::new(format!(
+            "{} Elapsed: {}    ETA: {}",
+            self.icon("⏱️ ", "[time]"),
+            scanning_state.format_elapsed(),
+            scanning_state.format_remaining()
+        ))
+        .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(elapsed, chunks[6]);
+
+        // Error message (if any)
+        if let Some(ref error) = scanning_state.error_message {
+            let error_msg = Paragraph::new(format!("{} Error: {}", self.icon("❌", "[x]"), error))
+                .style(Style::default().fg(Color::Red));
+            frame.render_widget(error_msg, chunks[7]);
+        }
+
+        // Controls
+        let controls_text = if scanning_state.error_message.is_some() {
+            "[Space] Retry  [ESC] Cancel"
+        } else if scanning_state.is_paused {
+            "[Space] Resume  [ESC] Cancel"
+        } else {
+            "[Space] Pause  [ESC] Cancel"
+        };
+        let controls = Paragraph::new(controls_text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[9]);
+    }
+
+    fn render_scan_complete_ui(&self, frame: &mut Frame) {
+        let scanning_state = self.get_scanning_state().unwrap_or_else(ScanningState::new);
+        let warnings = scanning_state.package_warnings.len();
+
+        let (title, border, summary) = if warnings == 0 && scanning_state.packages_found == 0 {
+            (
+                format!("{} Scan Complete", self.icon("✅", "[ok]")),
+                Color::Green,
+                "No packages installed — nothing to sweep.\nPress Enter or Space to continue."
+                    .to_string(),
+            )
+        } else if warnings == 0 {
+            (
+                format!("{} Scan Complete!", self.icon("✅", "[ok]")),
+                Color::Green,
+                "Scanning completed successfully!\nPress Enter or Space to view results."
+                    .to_string(),
+            )
+        } else {
+            (
+                format!(
+                    "{} Completed with {} warning{}",
+                    self.icon("⚠️ ", "[!]"),
+                    warnings,
+                    if warnings == 1 { "" } else { "s" }
+                ),
+                Color::Yellow,
+                format!(
+                    "Some packages could only be partly read.\nPress Enter or Space to view results, or w to see which {}.",
+                    if warnings == 1 { "one" } else { "ones" }
+                ),
+            )
+        };
+
+        let complete_block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2), // Summary
+                Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Packages found
+                Constraint::Length(1), // Time taken
+                Constraint::Length(1), // Sort mode
+                Constraint::Length(1), // Metadata warning
+                Constraint::Length(1), // Controls
+            ])
+            .split(complete_block.inner(frame.area()));
+
+        frame.render_widget(complete_block, frame.area());
+
+        // Summary
+        let summary = Paragraph::new(summary)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(summary, chunks[0]);
+
+        // Package count
+        let found = Paragraph::new(format!(
+            "{} Total Packages Found: {}",
+            self.icon("📦", "[pkg]"),
+            scanning_state.packages_found
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Green));
+        frame.render_widget(found, chunks[2]);
+
+        // Time taken
+        let time_taken = Paragraph::new(format!(
+            "{} Total Time: {}",
+            self.icon("⏱️ ", "[time]"),
+            scanning_state.format_elapsed()
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(time_taken, chunks[3]);
+
+        // Initial sort order for the table
+        let sort = Paragraph::new(format!(
+            "Sort by: {}",
+            self.sort_mode.label(self.sort_reversed)
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(sort, chunks[4]);
+
+        if let Some(ref warning) = scanning_state.warning {
+            let warning = Paragraph::new(format!("{} {}", self.icon("⚠️ ", "[!]"), warning))
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Red));
+            frame.render_widget(warning, chunks[5]);
+        }
+
+        // Controls
+        let controls = Paragraph::new(format!(
+            "[Enter/Space] View Results  [s] Sort: usage/name/type/size  [S] Reverse  {}[q] Quit",
+            if warnings == 0 { "" } else { "[w] Warnings  " }
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[6]);
+    }
+
+    fn render_top_sizes(&self, frame: &mut Frame, row: usize) {
+        let top_block = Block::default()
+            .title(format!("{} Top Space Consumers", self.icon("💾", "[size]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(2), // Summary
+                Constraint::Min(3),    // Largest packages
+                Constraint::Length(1), // Controls
+            ])
+            .split(top_block.inner(frame.area()));
+
+        frame.render_widget(top_block, frame.area());
+
+        let top = self.top_sizes();
+        let total: u64 = self.items.iter().filter_map(|p| p.size_bytes).sum();
+        let top_total: u64 = top.iter().filter_map(|&i| self.items[i].size_bytes).sum();
+        let share = |bytes: u64| {
+            if total == 0 {
+                0.0
+            } else {
+                bytes as f64 * 100.0 / total as f64
+            }
+        };
+
+        let summary = if top.is_empty() {
+            "No package sizes yet. Scan without --fast to measure them.".to_string()
+        } else {
+            format!(
+                "The {} largest of {} packages take {} of {} ({:.0}%).",
+                top.len(),
+                self.items.len(),
+                format_size(top_total),
+                format_size(total),
+                share(top_total)
+            )
+        };
+        let summary = Paragraph::new(summary)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(summary, chunks[0]);
+
+        let header = ["#", "Package Name", "Type", "Size", "Share"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(
+                Style::default()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+            );
+        let rows = top.iter().enumerate().map(|(rank, &index)| {
+            let package = &self.items[index];
+            let bytes = package.size_bytes.unwrap_or(0);
+            let color = match rank % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            };
+            Row::new([
+                format!("{:>2}", rank + 1),
+                package.name.clone(),
+                package.package_type().to_string(),
+                format!("{:>9}", format_size(bytes)),
+                format!("{:>5.1}%", share(bytes)),
+            ])
+            .style(Style::new().fg(self.colors.row_fg).bg(color))
+        });
+        let name_width = top
+            .iter()
+            .map(|&i| self.items[i].name.width())
+            .max()
+            .unwrap_or(0)
+            .max("Package Name".len()) as u16;
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(name_width + 2),
+                Constraint::Length(9),
+                Constraint::Length(11),
+                Constraint::Length(7),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(self.colors.selected_row_style_fg),
+        )
+        .highlight_symbol(" █ ")
+        .highlight_spacing(HighlightSpacing::Always)
+        .bg(self.colors.buffer_bg);
+        let mut state = TableState::default().with_selected(row);
+        frame.render_stateful_widget(table, chunks[1], &mut state);
+
+        let controls = Paragraph::new("[↑/↓] Move  [Enter] Show in Table  [t/Space/Esc] Back")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[2]);
+    }
+
+    fn render_no_brew(&self, frame: &mut Frame) {
+        let block = Block::default()
+            .title(format!("{} Homebrew Not Found", self.icon("⚠️ ", "[!]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Min(8),    // Explanation
+                Constraint::Length(1), // Controls
+            ])
+            .split(block.inner(frame.area()));
+
+        frame.render_widget(block, frame.area());
+
+        let command = Style::default().fg(Color::Cyan);
+        let mut lines = vec![
+            Line::from("brewsweep needs Homebrew, but there is no `brew` command on your PATH."),
+            Line::from(""),
+            Line::from("To install Homebrew, run:"),
+            Line::styled(
+                "/bin/bash -c \"$(curl -fsSL https://raw.githubusercontent.com/Homebrew/install/HEAD/install.sh)\"",
+                command,
+            ),
+            Line::from(""),
+            Line::from("If Homebrew is already installed, add it to your PATH, e.g.:"),
+            Line::styled("eval \"$(/opt/homebrew/bin/brew shellenv)\"", command),
+        ];
+        if let Some(ref message) = self.status_message {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(message.clone(), Color::Red));
+        }
+        let explanation = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(explanation, chunks[0]);
+
+        let controls = Paragraph::new("[r] Check Again  [q/Esc] Quit")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[1]);
+    }
+
+    fn render_scan_warnings(&self, frame: &mut Frame) {
+        let warnings = self
+            .get_scanning_state()
+            .map(|state| state.package_warnings)
+            .unwrap_or_default();
+
+        let warnings_block = Block::default()
+            .title(format!("{} Scan Warnings", self.icon("⚠️ ", "[!]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2), // Summary
+                Constraint::Min(3),    // Affected packages
+                Constraint::Length(1), // Controls
+            ])
+            .split(warnings_block.inner(frame.area()));
+
+        frame.render_widget(warnings_block, frame.area());
+
+        let summary = Paragraph::new(if warnings.len() == 1 {
+            "1 package could only be partly read. It is still listed, with less detail.".to_string()
+        } else {
+            format!(
+                "{} packages could only be partly read. They are still listed, with less detail.",
+                warnings.len()
+            )
+        })
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(summary, chunks[0]);
+
+        let name_width = warnings
+            .iter()
+            .map(|(name, _)| name.width())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = warnings
+            .iter()
+            .map(|(name, reason)| Line::from(format!("{:<name_width$}  {}", name, reason)))
+            .collect();
+        let list = Paragraph::new(lines).style(Style::default().fg(Color::Yellow));
+        frame.render_widget(list, chunks[1]);
+
+        let controls = Paragraph::new("[n/Space] Back")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[2]);
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
+        if self.items.is_empty() {
+            let empty_msg = Paragraph::new(if self.has_scanned {
+                "No packages installed — nothing to sweep. Press r to scan again."
+            } else {
+                "No packages found. Press Space to start scanning."
+            })
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray))
+            .block(
+                Block::default()
+                    .title("Homebrew Packages")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.colors.footer_border_color)),
+            );
+            frame.render_widget(empty_msg, area);
+            return;
+        }
+
+        if self.visible_row_count() == 0 {
+            let message = if self.filter.is_empty() {
+                "All packages are hidden. Press X, U or O to clear the filters.".to_string()
+            } else {
+                format!(
+                    "No packages match \"{}\". Press Esc to clear the search.",
+                    self.filter
+                )
+            };
+            let empty_msg = Paragraph::new(message)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Gray))
+                .block(
+                    Block::default()
+                        .title(self.table_title())
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(self.colors.footer_border_color)),
+                );
+            frame.render_widget(empty_msg, area);
+            return;
+        }
+
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+
+        let selected_col_style = Style::default().fg(self.colors.selected_column_style_fg);
+
+        let selected_cell_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_cell_style_fg);
+
+        let mut header: Vec<String> = [
+            "Package Name",
+            "Type",
+            "Version",
+            "Last Accessed",
+            "Last Accessed Path",
+            "Size",
+            "Orphans",
+        ]
+        .map(String::from)
+        .into();
+        let sorted = &mut header[self.sort_mode.column()];
+        *sorted = format!("{} {}", sorted, self.sort_arrow());
+        if self.show_idle_days {
+            header.insert(IDLE_COLUMN, "Days Idle".to_string());
+        }
+        let pad = " ".repeat(self.horizontal_padding() as usize);
+        // Sizes line up on the right, under a header ending in the same place
+        let size_column = 5 + usize::from(self.show_idle_days);
+        let header = header
+            .into_iter()
+            .enumerate()
+            .map(|(col, label)| {
+                if col == size_column {
+                    Cell::from(Line::from(format!("{label}{pad}")).alignment(Alignment::Right))
+                } else {
+                    Cell::from(label)
+                }
+            })
+            .collect::<Row>()
+            .style(header_style)
+            .height(1);
+
+        let item_height = self.item_height();
+
+        let rows = self.filtered_indices.iter().enumerate().map(|(i, &index)| {
+            let package = &self.items[index];
+            let color = match i % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            };
+            let favorite = self.favorites.contains(&package.name);
+            let mut marks = Vec::new();
+            if favorite {
+                marks.push(Span::styled(
+                    self.icon("★", "@"),
+                    Style::new().fg(self.colors.favorite_fg),
+                ));
+            }
+            if self.marked.contains(&index) {
+                marks.push(Span::styled(
+                    self.icon("●", "*"),
+                    Style::new().fg(self.colors.selected_row_style_fg),
+                ));
+            }
+            if let Some(status) = self.batch_status.get(&index) {
+                marks.push(Span::styled(
+                    status.glyph(self.config.ascii_mode),
+                    Style::new().fg(status.color()),
+                ));
+            }
+            if self.autoremovable.contains(&package.name) {
+                marks.push(Span::styled(
+                    self.icon("♻", "o"),
+                    Style::new().fg(Color::LightMagenta),
+                ));
+            }
+            if package.unused_since_install() {
+                marks.push(Span::styled(
+                    self.icon("∅", "0"),
+                    Style::new().fg(Color::LightRed),
+                ));
+            }
+            if package.outdated {
+                marks.push(Span::styled(
+                    self.icon("⬆", "^"),
+                    Style::new().fg(Color::LightCyan),
+                ));
+            }
+            if self.is_pinned(package) {
+                marks.push(Span::styled(
+                    self.icon("🔒", "L"),
+                    Style::new().fg(Color::LightYellow),
+                ));
+            }
+            if self.is_kept(package) {
+                marks.push(Span::styled(
+                    self.icon("⚓", "k"),
+                    Style::new().add_modifier(Modifier::DIM),
+                ));
+            }
+            if self.is_reviewed(package) {
+                marks.push(Span::styled(
+                    self.icon("✓", "+"),
+                    Style::new().add_modifier(Modifier::DIM),
+                ));
+            }
+            let row_fg = if favorite {
+                self.colors.favorite_fg
+            } else {
+                staleness_color(package)
+            };
+            let mut item = package.get_display_fields();
+            if self.short_paths {
+                item[4] = short_path(&item[4]).to_string();
+            }
+            if package.time_source == TimeSource::Mtime {
+                item[3] = format!("{} {}", item[3], self.icon("≈", "~"));
+            }
+            if self.show_idle_days {
+                let days = package
+                    .days_idle()
+                    .map_or_else(|| "-".to_string(), |days| days.to_string());
+                let days = format!("{:>9}", days);
+                item.insert(IDLE_COLUMN, days);
+            }
+            item.into_iter()
+                .enumerate()
+                .map(|(col, content)| {
+                    let line = if col == 0 && !marks.is_empty() {
+                        let mut spans = vec![Span::raw(pad.clone())];
+                        for mark in &marks {
+                            spans.push(mark.clone());
+                            spans.push(Span::raw(" "));
+                        }
+                        spans.push(Span::raw(format!("{content}{pad}")));
+                        Line::from(spans)
+                    } else if col == size_column {
+                        Line::from(format!("{pad}{content}{pad}")).alignment(Alignment::Right)
+                    } else {
+                        Line::from(format!("{pad}{content}{pad}"))
+                    };
+                    self.padded_cell(line)
+                })
+                .collect::<Row>()
+                .style(Style::new().fg(row_fg).bg(color))
+                .height(item_height as u16)
+        });
+
+        let summary_row = self.small_group.map(|(count, bytes)| {
+            let color = match self.filtered_indices.len() % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            };
+            let action = if self.small_group_expanded {
+                "Enter to collapse"
+            } else {
+                "Enter to expand"
+            };
+            let mut cells = vec![
+                format!("{} small packages", count),
+                format!("< {} MB", self.config.small_package_threshold_mb),
+                String::new(),
+                format!("{} total", format_size(bytes)),
+                action.to_string(),
+                String::new(),
+                String::new(),
+            ];
+            if self.show_idle_days {
+                cells.insert(IDLE_COLUMN, String::new());
+            }
+            cells
+                .into_iter()
+                .map(|content| self.padded_cell(Line::from(format!("{pad}{content}{pad}"))))
+                .collect::<Row>()
+                .style(
+                    Style::new()
+                        .fg(self.colors.row_fg)
+                        .bg(color)
+                        .add_modifier(Modifier::ITALIC),
+                )
+                .height(item_height as u16)
+        });
+        let rows = rows.chain(summary_row);
+
+        let bar = " █ ";
+        let padding = self.vertical_padding();
+        let mut highlight_lines = vec![Line::default(); padding];
+        highlight_lines.push(Line::from(bar));
+        highlight_lines.resize(2 * padding + 1, Line::default());
+        let highlight_symbol = Text::from(highlight_lines);
+        // Column widths below assume one space of padding on each side
+        let extra = (2 * self.horizontal_padding()).saturating_sub(2);
+
+        let mut widths = vec![
+            Constraint::Length(self.longest_item_lens.0 + 10 + extra),
+            Constraint::Min(self.longest_item_lens.1 + 3 + extra),
+            Constraint::Min(self.longest_item_lens.4 + extra),
+            Constraint::Min(self.longest_item_lens.3 + extra),
+            Constraint::Min(self.longest_item_lens.2 + extra),
+            Constraint::Length(11 + extra),
+            Constraint::Length(9 + extra),
+        ];
+        if self.show_idle_days {
+            widths.insert(IDLE_COLUMN, Constraint::Length(11 + extra));
+        }
+
+        let t = Table::new(rows, widths)
+            .block(
+                Block::default()
+                    .title(self.table_title())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.colors.footer_border_color)),
+            )
+            .header(header)
+            .row_highlight_style(selected_row_style)
+            .column_highlight_style(selected_col_style)
+            .cell_highlight_style(selected_cell_style)
+            .highlight_symbol(highlight_symbol)
+            .bg(self.colors.buffer_bg)
+            .highlight_spacing(HighlightSpacing::Always);
+
+        frame.render_stateful_widget(t, area, &mut self.state);
+    }
+
+    /// "Homebrew Packages — 312 total (280 formulae, 32 casks)", leading with
+    /// the number shown when filters hide some packages.
+    fn table_title(&self) -> String {
+        let (formulae, casks) = type_counts(&self.items);
+        let total = self.items.len();
+        let shown = self.filtered_indices.len() + self.small_group.map_or(0, |(count, _)| count);
+        let count = if shown == total {
+            format!("{} total", total)
+        } else {
+            format!("{} of {} shown", shown, total)
+        };
+        format!(
+            "Homebrew Packages — {} ({} {}, {} {})",
+            count,
+            formulae,
+            if formulae == 1 { "formula" } else { "formulae" },
+            casks,
+            if casks == 1 { "cask" } else { "casks" }
+        )
+    }
+
+    fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            // Over the table's right border, below the header
+            area.inner(Margin {
+                vertical: 2,
+                horizontal: 0,
+            }),
+            &mut self.scroll_state,
+        );
+    }
+
+    /// Short summary of the active view settings, shown under the key hints.
+    fn status_line(&self) -> String {
+        let mut parts = Vec::new();
+        if self.searching {
+            parts.push(format!(
+                "Search: {}{} (Enter) keep | (Esc) clear",
+                self.filter,
+                self.icon("▏", "_")
+            ));
+        } else if !self.filter.is_empty() {
+            parts.push(format!(
+                "Matching \"{}\": {} | (Esc) clear",
+                self.filter,
+                self.filtered_indices.len()
+            ));
+        }
+        if let Some(scanned_at) = self.scanned_at.filter(|_| self.from_cache) {
+            parts.push(format!(
+                "Cached ({}) | (r) rescan",
+                format_elapsed(scanned_at)
+            ));
+        }
+        if self.dry_run {
+            parts.push("Dry run (N)".to_string());
+        }
+        match self.config.scan_scope {
+            ScanScope::All => {}
+            ScanScope::Formulae => parts.push("Formulae only".to_string()),
+            ScanScope::Casks => parts.push("Casks only".to_string()),
+        }
+        if self.hide_reviewed {
+            let hidden = self.items.iter().filter(|p| self.is_reviewed(p)).count();
+            parts.push(format!("Hiding {} reviewed", hidden));
+        }
+        if self.only_unused {
+            parts.push("Only unused since install".to_string());
+        }
+        if self.only_outdated {
+            parts.push(format!("Only outdated: {}", self.filtered_indices.len()));
+        }
+        if self.idle_filter != IdleFilter::All {
+            parts.push(format!(
+                "{}: {}",
+                self.idle_filter.label(),
+                self.filtered_indices.len()
+            ));
+        }
+        if let Some(range) = self.size_filter {
+            let (count, total) = self.visible_totals();
+            parts.push(format!(
+                "Size {}: {} package{}, {}",
+                range.label(),
+                count,
+                if count == 1 { "" } else { "s" },
+                format_size(total)
+            ));
+        }
+        if self
+            .palette_switched
+            .is_some_and(|switched| switched.elapsed() < PALETTE_NOTICE)
+        {
+            parts.push(format!("Palette: {}", PALETTE_NAMES[self.color_index]));
+        }
+        if let Some(since) = self.watching_since {
+            let next = self.watch_interval().saturating_sub(since.elapsed());
+            parts.push(format!("Watching ({}s)", next.as_secs_f64().ceil()));
+        }
+        parts.join(" | ")
+    }
+
+    fn render_footer(&self, frame: &mut Frame, area: Rect) {
+        let mut lines: Vec<Line> = INFO_TEXT.iter().map(|&text| Line::from(text)).collect();
+        let status = self.status_line();
+        if !status.is_empty() {
+            lines.push(Line::from(status).fg(Color::Yellow));
+        }
+        if let Some(ref message) = self.status_message {
+            let color = if self.status_success {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            let mut message = message.clone();
+            if self.retry_target.is_some() {
+                message.push_str(" | (F) retry with --force");
+            }
+            lines.push(Line::from(message).fg(color));
+        }
+
+        let info_footer = Paragraph::new(Text::from(lines))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .centered()
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Double)
+                    .border_style(Style::new().fg(self.colors.footer_border_color)),
+            );
+        frame.render_widget(info_footer, area);
+    }
+
+    fn render_package_details(&self, frame: &mut Frame, package_index: usize) {
+        if package_index >= self.items.len() {
+            return;
+        }
+
+        let package = &self.items[package_index];
+        let extended = self.shows_extended_details(package);
+        let breakdown_rows = package.paths.len().min(5) as u16;
+        let deps = match self.current_deps {
+            Some((ref name, ref deps)) if *name == package.name => Some(deps),
+            _ => None,
+        };
+        let deps_rows = match deps {
+            Some(Ok(deps)) if !deps.is_empty() => deps.len().min(DEPS_ROWS) as u16,
+            _ => 0,
+        };
+
+        let details_block = Block::default()
+            .title(format!(
+                "{} Package Details: {}",
+                self.icon("📦", "[pkg]"),
+                package.name
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2), // Name and type
+                Constraint::Length(3), // Last accessed, install date and size
+                Constraint::Length(
+                    2 + u16::from(package.homepage().is_some())
+                        + u16::from(!package.used_options().is_empty()),
+                ), // Path, license, homepage and options
+                Constraint::Length(deps_rows + 2), // Dependencies
+                Constraint::Length(if extended { breakdown_rows + 3 } else { 0 }), // Extended
+                Constraint::Min(1),    // Verify result
+                Constraint::Length(1), // Reveal or homepage result
+                Constraint::Length(1), // Controls
+            ])
+            .split(details_block.inner(frame.area()));
+
+        frame.render_widget(details_block, frame.area());
+
+        // Package name and type
+        let name_type = Paragraph::new(format!(
+            "Name: {}\nType: {}    Version: {}{}{}",
+            package.name,
+            package.package_type(),
+            package.version.as_deref().unwrap_or("Unknown"),
+            if package.outdated {
+                format!(" {} update available (u to upgrade)", self.icon("⬆", "^"))
+            } else {
+                String::new()
+            },
+            if self.is_pinned(package) {
+                format!("    {} Pinned (p to unpin)", self.icon("🔒", "[L]"))
+            } else {
+                String::new()
+            }
+        ))
+        .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(name_type, chunks[0]);
+
+        // Last accessed, install date and size
+        let mut size = package.format_size();
+        if let Some((ref name, Some(reported))) = self.reported_size {
+            if *name == package.name && sizes_differ(package.size_bytes, reported) {
+                size = format!(
+                    "{} on disk vs {} reported by brew (shared or linked files)",
+                    size,
+                    format_size(reported)
+                );
+            }
+        }
+        let mut last_accessed = package.format_last_accessed();
+        if let (true, Some(time)) = (self.show_dates, package.last_accessed) {
+            last_accessed = format_relative_with_date(&last_accessed, time);
+        }
+        let installed = package.installed_at.map_or_else(
+            || "Unknown".to_string(),
+            |time| format_relative_with_date(&format_elapsed(time), time),
+        );
+        let accessed = Paragraph::new(format!(
+            "Last Accessed: {}{}\nInstalled: {}\nSize: {}",
+            last_accessed,
+            match package.time_source {
+                TimeSource::Atime => String::new(),
+                TimeSource::Mtime => format!(
+                    " ({} last modified; access times aren't recorded for this path)",
+                    self.icon("≈", "~")
+                ),
+            },
+            installed,
+            size
+        ))
+        .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(accessed, chunks[1]);
+
+        // Path, license, homepage and install options
+        let mut lines = vec![
+            Line::from(format!(
+                "Path: {}",
+                package.last_accessed_path.as_deref().unwrap_or("Unknown")
+            )),
+            Line::from(format!("License: {}", package.license())),
+        ];
+        if let Some(homepage) = package.homepage() {
+            lines.push(Line::from(format!("Homepage: {}", homepage)));
+        }
+        let options = package.used_options();
+        if !options.is_empty() {
+            let mut spans = vec![Span::raw(format!("Installed with: {}", options.join(" ")))];
+            if options.iter().any(|option| option == "--HEAD") {
+                spans.push(Span::styled(
+                    format!(
+                        "  {} HEAD build from the latest source, not a release",
+                        self.icon("⚠️", "[!]")
+                    ),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        let path = Paragraph::new(lines).style(Style::default().fg(Color::Cyan));
+        frame.render_widget(path, chunks[2]);
+
+        // Direct dependencies, scrolled with ↑/↓ when there are more than fit
+        let mut lines = vec![Line::from("Dependencies:")];
+        match deps {
+            Some(Ok(deps)) if deps.is_empty() => lines.push(Line::from("  No dependencies")),
+            Some(Ok(deps)) => {
+                if deps.len() > DEPS_ROWS {
+                    lines[0] = Line::from(format!(
+                        "Dependencies ({}-{} of {}, ↑/↓ to scroll):",
+                        self.deps_scroll + 1,
+                        self.deps_scroll + DEPS_ROWS,
+                        deps.len()
+                    ));
+                }
+                lines.extend(
+                    deps.iter()
+                        .skip(self.deps_scroll)
+                        .take(DEPS_ROWS)
+                        .map(|dep| Line::from(format!("  {}", dep))),
+                );
+            }
+            Some(Err(e)) => lines.push(Line::from(format!("  {}", e))),
+            None => lines.push(Line::from("  Loading...")),
+        }
+        let dependencies = Paragraph::new(lines).style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(dependencies, chunks[3]);
+
+        // Dependents and size breakdown, for large packages or on request
+        if extended {
+            let mut lines = vec![
+                format!(
+                    "Dependents: {}",
+                    if package.dependents.is_empty() {
+                        "none".to_string()
+                    } else {
+                        package.dependents.join(", ")
+                    }
+                ),
+                String::new(),
+                "Size breakdown:".to_string(),
+            ];
+            match self.size_breakdown {
+                Some((ref name, ref sizes)) if *name == package.name => {
+                    lines.extend(
+                        sizes
+                            .iter()
+                            .take(breakdown_rows as usize)
+                            .map(|(path, size)| format!("  {:>10}  {}", format_size(*size), path)),
+                    );
+                }
+                _ => lines.push("  Calculating...".to_string()),
+            }
+            let details = Paragraph::new(lines.join("\n")).style(Style::default().fg(Color::Cyan));
+            frame.render_widget(details, chunks[4]);
+        }
+
+        // Verify result (only for this package)
+        if let Some((ref name, ref missing)) = self.verify_result {
+            if *name == package.name {
+                let verify = if missing.is_empty() {
+                    Paragraph::new(format!(
+                        "{} All {} recorded paths present on disk",
+                        self.icon("✅", "[ok]"),
+                        package.paths.len()
+                    ))
+                    .style(Style::default().fg(Color::Green))
+                } else {
+                    let mut lines = vec![format!(
+                        "{} Missing {} path(s):",
+                        self.icon("❌", "[x]"),
+                        missing.len()
+                    )];
+                    lines.extend(missing.iter().map(|path| format!("  {}", path)));
+                    lines.push(String::new());
+                    lines.push(format!(
+                        "Install looks broken, try: brew reinstall {}",
+                        package.name
+                    ));
+                    Paragraph::new(lines.join("\n")).style(Style::default().fg(Color::Red))
+                };
+                frame.render_widget(verify, chunks[5]);
+            }
+        }
+
+        // Reveal or homepage result (only for this package)
+        if let Some((ref name, ref result)) = self.open_result {
+            if *name == package.name {
+                let reveal = match result {
+                    Ok(opened) => Paragraph::new(*opened).style(Style::default().fg(Color::Green)),
+                    Err(e) => Paragraph::new(format!("{} {}", self.icon("❌", "[x]"), e))
+                        .style(Style::default().fg(Color::Red)),
+                };
+                frame.render_widget(reveal, chunks[6]);
+            }
+        }
+
+        // Controls
+        let controls = Paragraph::new(format!(
+            "[Enter/Space/ESC] Back  [d] Delete  [v] Verify Files  [r] Reveal  [o] Homepage  [u] Upgrade  [R] Refresh  [t] {}  [i] {}  [q] Quit",
+            if self.show_dates { "Hide Dates" } else { "Show Dates" },
+            if self.full_details {
+                "Auto Detail"
+            } else {
+                "Full Detail"
+            }
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[7]);
+    }
+
+    fn render_confirm_delete(&self, frame: &mut Frame, package_index: usize) {
+        if package_index >= self.items.len() {
+            return;
+        }
+
+        let package = &self.items[package_index];
+
+        let confirm_block = Block::default()
+            .title(format!(
+                "{} Confirm Delete{}",
+                self.icon("⚠️ ", "[!]"),
+                self.dry_run_tag()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(3), // Warning message
+                Constraint::Length(2), // Dependents
+                Constraint::Length(2), // Package info
+                Constraint::Length(1), // Hold progress
+                Constraint::Length(1), // Controls
+            ])
+            .split(confirm_block.inner(frame.area()));
+
+        frame.render_widget(confirm_block, frame.area());
+
+        // Warning message
+        let warning = Paragraph::new(format!(
+            "Are you sure you want to delete '{}'{}?\n\n{}",
+            package.name,
+            if self.delete_zap {
+                " and zap its preferences and support files"
+            } else {
+                ""
+            },
+            if self.dry_run {
+                "Dry run: this only lists what would be removed."
+            } else {
+                "This action cannot be undone!"
+            }
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Red));
+        frame.render_widget(warning, chunks[0]);
+
+        // Dependents
+        let dependents = if !self.confirm_dependents.is_empty() {
+            Paragraph::new(format!(
+                "{} Warning: required by {}",
+                self.icon("⚠️ ", "[!]"),
+                self.confirm_dependents.join(", ")
+            ))
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        } else if self.dependents_receiver.is_some() {
+            Paragraph::new("Checking installed dependents...")
+                .style(Style::default().fg(Color::Gray))
+        } else {
+            Paragraph::new("")
+        };
+        frame.render_widget(
+            dependents
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        );
+
+        // Package info
+        let info = Paragraph::new(format!(
+            "Type: {}\nPath: {}",
+            package.package_type(),
+            package.last_accessed_path.as_deref().unwrap_or("Unknown")
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(info, chunks[2]);
+
+        // Hold progress
+        let mut controls = if self.config.hold_to_confirm {
+            let hold = Gauge::default()
+                .gauge_style(Style::default().fg(Color::Red))
+                .ratio(self.confirm_hold_progress())
+                .label("Hold y or Enter to delete");
+            frame.render_widget(hold, chunks[3]);
+            "[hold y] Delete  [n] No, Cancel  [hold Enter] Delete  [Space] Cancel".to_string()
+        } else {
+            "[y] Yes, Delete  [n] No, Cancel  [Enter] Delete  [Space] Cancel".to_string()
+        };
+        if package.package_type == PackageType::Cask {
+            controls.push_str(&format!(
+                "  [z] --zap: {}",
+                if self.delete_zap { "on" } else { "off" }
+            ));
+        }
+
+        // Controls
+        let controls = Paragraph::new(controls)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[4]);
+    }
+
+    fn render_deleting(&self, frame: &mut Frame, package_index: usize) {
+        if package_index >= self.items.len() {
+            return;
+        }
+
+        let package = &self.items[package_index];
+
+        let deleting_block = Block::default()
+            .title(format!(
+                "{} Uninstalling Package{}",
+                self.icon("🗑️ ", "[del]"),
+                self.dry_run_tag()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
             .style(Style::default().bg(self.colors.buffer_bg));
 
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
-            .margin(2)
+            .margin(1)
             .constraints([
-                Constraint::Length(1), // Status
-                Constraint::Length(1), // Empty space
-                Constraint::Length(3), // Progress bar
-                Constraint::Length(1), // Empty space
-                Constraint::Length(1), // Packages found
-                Constraint::Length(1), // Current scanning
-                Constraint::Length(1), // Elapsed time
-                Constraint::Length(1), // Error message (if any)
-                Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Package info
+                Constraint::Length(1), // Empty line
+                Constraint::Min(5),    // Command output
                 Constraint::Length(1), // Controls
             ])
-            .split(scanning_block.inner(frame.area()));
+            .split(deleting_block.inner(frame.area()));
 
-        frame.render_widget(scanning_block, frame.area());
+        frame.render_widget(deleting_block, frame.area());
 
-        // Status text
-        let status_text = if let Some(ref error) = scanning_state.error_message {
-            format!("Error: {}", error)
-        } else if scanning_state.is_paused {
-            "Status: Scanning paused...".to_string()
+        // Package info
+        let package_info = Paragraph::new(format!(
+            "{}: {} ({})",
+            if self.delete_running() {
+                "Uninstalling"
+            } else {
+                "Dry run finished"
+            },
+            package.name,
+            package.package_type()
+        ))
+        .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(package_info, chunks[0]);
+
+        // Command output
+        self.render_command_output(frame, chunks[2], "Starting uninstall process...");
+
+        // Controls
+        let controls = Paragraph::new(if self.delete_running() {
+            "[Space] Cancel  [c] Stop Watching  [q] Force Quit"
         } else {
-            "Status: Scanning installed packages...".to_string()
+            "[Enter/Space/ESC] Back  [q] Quit"
+        })
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[3]);
+    }
+
+    /// Streams the captured brew output, following the tail unless it has
+    /// been scrolled up.
+    fn render_command_output(&self, frame: &mut Frame, area: Rect, placeholder: &str) {
+        let output_text = if self.delete_output.is_empty() {
+            placeholder.to_string()
+        } else {
+            self.delete_output.join("\n")
         };
 
-        let status_color = if scanning_state.error_message.is_some() {
-            Color::Red
+        let height = area.height.saturating_sub(2) as usize;
+        let tail = self.delete_output.len().saturating_sub(height);
+        let title = if self.output_scroll_back > 0 {
+            format!(
+                "Command Output ({} more lines below, ↓ to follow)",
+                self.output_scroll_back.min(tail)
+            )
+        } else if tail > 0 {
+            "Command Output (↑/↓ to scroll)".to_string()
         } else {
-            self.colors.row_fg
+            "Command Output".to_string()
         };
+        let output_block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
 
-        let status = Paragraph::new(status_text).style(Style::default().fg(status_color));
-        frame.render_widget(status, chunks[0]);
+        let output_paragraph = Paragraph::new(output_text)
+            .block(output_block)
+            .style(Style::default().fg(Color::Green))
+            .scroll((tail.saturating_sub(self.output_scroll_back) as u16, 0));
 
-        // Progress bar
-        let progress = Gauge::default()
-            .block(Block::default().title("Progress").borders(Borders::ALL))
-            .gauge_style(Style::default().fg(self.colors.footer_border_color))
-            .percent(scanning_state.progress_percentage())
-            .label(format!(
-                "{}% ({}/{})",
-                scanning_state.progress_percentage(),
-                scanning_state.packages_scanned,
-                scanning_state.total_packages
-            ));
-        frame.render_widget(progress, chunks[2]);
+        frame.render_widget(output_paragraph, area);
+    }
 
-        // Package count
-        let found = Paragraph::new(format!(
-            "📦 Packages Found: {}",
-            scanning_state.packages_found
+    fn render_running_maintenance(&self, frame: &mut Frame, action: &MaintenanceAction) {
+        let running_block = Block::default()
+            .title(format!("{} {}", self.icon("🧹", "[maint]"), action.label()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(self.colors.buffer_bg));
+
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(1), // Action
+                Constraint::Length(1), // Empty line
+                Constraint::Min(5),    // Command output
+            ])
+            .split(running_block.inner(frame.area()));
+
+        frame.render_widget(running_block, frame.area());
+
+        let info = Paragraph::new(format!(
+            "Running: {}  [Space] Cancel  [q] Force Quit",
+            action.command()
         ))
-        .style(Style::default().fg(Color::Green));
-        frame.render_widget(found, chunks[4]);
+        .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(info, chunks[0]);
 
-        // Current scanning
-        let current = Paragraph::new(format!("📁 Current: {}", scanning_state.current_path))
-            .style(Style::default().fg(Color::Yellow));
-        frame.render_widget(current, chunks[5]);
+        self.render_command_output(frame, chunks[2], "Starting...");
+    }
 
-        // Elapsed time
-        let elapsed = Paragraph::new(format!("⏱️  Elapsed: {}", scanning_state.format_elapsed()))
-            .style(Style::default().fg(Color::Cyan));
-        frame.render_widget(elapsed, chunks[6]);
+    /// A red confirm screen: a warning, the list of what will be removed, and controls.
+    fn render_confirm(
+        &self,
+        frame: &mut Frame,
+        title: &str,
+        warning: String,
+        items: Vec<Line>,
+        controls: &str,
+    ) {
+        let confirm_block = Block::default()
+            .title(format!("{} {}", self.icon("⚠️ ", "[!]"), title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(self.colors.buffer_bg));
 
-        // Error message (if any)
-        if let Some(ref error) = scanning_state.error_message {
-            let error_msg = Paragraph::new(format!("❌ Error: {}", error))
-                .style(Style::default().fg(Color::Red));
-            frame.render_widget(error_msg, chunks[7]);
-        }
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(3), // Warning message
+                Constraint::Min(3),    // Item list
+                Constraint::Length(1), // Controls
+            ])
+            .split(confirm_block.inner(frame.area()));
 
-        // Controls
-        let controls_text = if scanning_state.error_message.is_some() {
-            "[Space] Retry  [ESC] Cancel"
-        } else if scanning_state.is_paused {
-            "[Space] Resume  [ESC] Cancel"
-        } else {
-            "[Space] Pause  [ESC] Cancel"
-        };
-        let controls = Paragraph::new(controls_text)
+        frame.render_widget(confirm_block, frame.area());
+
+        let warning = Paragraph::new(warning)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Red));
+        frame.render_widget(warning, chunks[0]);
+
+        let list = Paragraph::new(items)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(list, chunks[1]);
+
+        let controls = Paragraph::new(controls)
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Gray));
-        frame.render_widget(controls, chunks[9]);
+        frame.render_widget(controls, chunks[2]);
     }
 
-    fn render_scan_complete_ui(&self, frame: &mut Frame) {
-        let scanning_state = self.get_scanning_state().unwrap_or_else(ScanningState::new);
+    fn render_confirm_batch_delete(&self, frame: &mut Frame) {
+        let indices = self.marked_indices();
+        let large = self.is_large_batch();
 
-        let complete_block = Block::default()
-            .title("✅ Scan Complete!")
+        let warning = format!(
+            "Uninstall {} packages ({}){}?\n\n{}",
+            indices.len(),
+            format_size(self.marked_total_size()),
+            if self.batch_force {
+                " with --force, removing every installed version"
+            } else {
+                ""
+            },
+            if self.dry_run {
+                "Dry run: this only lists what would be removed."
+            } else if self.large_batch_armed {
+                "This is a large batch. Press y or Enter again to really delete them all."
+            } else if large {
+                "This is a large batch and cannot be undone! You will be asked twice."
+            } else {
+                "This action cannot be undone!"
+            }
+        );
+
+        let names: Vec<Line> = indices
+            .iter()
+            .map(|&i| {
+                let package = &self.items[i];
+                Line::from(format!(
+                    "{} ({}, {})",
+                    package.name,
+                    package.package_type(),
+                    package.format_size()
+                ))
+            })
+            .collect();
+
+        self.render_confirm(
+            frame,
+            &format!("Confirm Batch Delete{}", self.dry_run_tag()),
+            warning,
+            names,
+            &format!(
+                "{}  [f] --force: {}  [n/Space] Cancel",
+                if self.large_batch_armed {
+                    "[y/Enter] Yes, Delete All"
+                } else if large {
+                    "[y/Enter] Continue (1 of 2)"
+                } else {
+                    "[y/Enter] Delete All"
+                },
+                if self.batch_force { "on" } else { "off" }
+            ),
+        );
+    }
+
+    fn render_maintenance_menu(&self, frame: &mut Frame) {
+        let menu_block = Block::default()
+            .title(format!("{} Maintenance", self.icon("🧹", "[maint]")))
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Green))
+            .border_style(Style::default().fg(self.colors.footer_border_color))
             .style(Style::default().bg(self.colors.buffer_bg));
 
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .margin(2)
             .constraints([
-                Constraint::Length(2), // Summary
-                Constraint::Length(1), // Empty space
-                Constraint::Length(1), // Packages found
-                Constraint::Length(1), // Time taken
-                Constraint::Length(1), // Empty space
+                Constraint::Length(2), // Intro
+                Constraint::Min(3),    // Actions
                 Constraint::Length(1), // Controls
             ])
-            .split(complete_block.inner(frame.area()));
+            .split(menu_block.inner(frame.area()));
 
-        frame.render_widget(complete_block, frame.area());
+        frame.render_widget(menu_block, frame.area());
 
-        // Summary
-        let summary = Paragraph::new(
-            "Scanning completed successfully!\nPress Enter or Space to view results.",
+        let intro = Paragraph::new(
+            "Homebrew-wide cleanup. Each action shows a dry run before anything is removed.",
         )
         .alignment(Alignment::Center)
         .style(Style::default().fg(self.colors.row_fg));
-        frame.render_widget(summary, chunks[0]);
-
-        // Package count
-        let found = Paragraph::new(format!(
-            "📦 Total Packages Found: {}",
-            scanning_state.packages_found
-        ))
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Green));
-        frame.render_widget(found, chunks[2]);
+        frame.render_widget(intro, chunks[0]);
 
-        // Time taken
-        let time_taken = Paragraph::new(format!(
-            "⏱️  Total Time: {}",
-            scanning_state.format_elapsed()
-        ))
+        let actions = Paragraph::new(vec![
+            Line::from("[c] Cleanup      remove old versions and stale downloads"),
+            Line::from("[a] Autoremove   remove dependencies nothing needs anymore"),
+            Line::from(format!(
+                "[p] Prune Cache  empty the entire download cache ({})",
+                match self.cache_size {
+                    None => "measuring...".to_string(),
+                    Some(Ok((ref dir, size))) =>
+                        format!("{} at {}", format_size(size), dir.display()),
+                    Some(Err(ref e)) => e.clone(),
+                }
+            )),
+            Line::from(format!(
+                "[b] Restore      reinstall everything in {}",
+                self.config.brewfile
+            )),
+            Line::from(format!(
+                "[l] Broken Links {} dangling symlink{} in the Homebrew bin",
+                self.broken_links.len(),
+                if self.broken_links.len() == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            )),
+        ])
         .alignment(Alignment::Center)
         .style(Style::default().fg(Color::Cyan));
-        frame.render_widget(time_taken, chunks[3]);
+        frame.render_widget(actions, chunks[1]);
 
-        // Controls
-        let controls = Paragraph::new("[Enter/Space] View Results  [ESC] Quit")
+        let controls = Paragraph::new("[c/a/p/b/l] Choose  [n/Space] Back")
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Gray));
-        frame.render_widget(controls, chunks[5]);
+        frame.render_widget(controls, chunks[2]);
     }
 
-    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
-        if self.items.is_empty() {
-            let empty_msg = Paragraph::new("No packages found. Press Space to start scanning.")
-                .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::Gray))
-                .block(
-                    Block::default()
-                        .title("Homebrew Packages")
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(self.colors.footer_border_color)),
-                );
-            frame.render_widget(empty_msg, area);
-            return;
-        }
-
-        let header_style = Style::default()
-            .fg(self.colors.header_fg)
-            .bg(self.colors.header_bg);
+    fn render_history(&self, frame: &mut Frame) {
+        let history_block = Block::default()
+            .title(format!("{} Footprint History", self.icon("📈", "[hist]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
 
-        let selected_row_style = Style::default()
-            .add_modifier(Modifier::REVERSED)
-            .fg(self.colors.selected_row_style_fg);
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2), // Summary
+                Constraint::Length(6), // Package count trend
+                Constraint::Length(6), // Size trend
+                Constraint::Min(3),    // Dated entries
+                Constraint::Length(1), // Controls
+            ])
+            .split(history_block.inner(frame.area()));
 
-        let selected_col_style = Style::default().fg(self.colors.selected_column_style_fg);
+        frame.render_widget(history_block, frame.area());
 
-        let selected_cell_style = Style::default()
-            .add_modifier(Modifier::REVERSED)
-            .fg(self.colors.selected_cell_style_fg);
+        let summary = match (self.history.first(), self.history.last()) {
+            (Some(first), Some(last)) if self.history.len() > 1 => format!(
+                "{} scans since {}: {} packages now, {:+} overall.",
+                self.history.len(),
+                format_timestamp(first.time),
+                last.packages,
+                last.packages as i64 - first.packages as i64
+            ),
+            (Some(only), _) => format!(
+                "One scan recorded ({}). The trend fills in as you rescan.",
+                format_timestamp(only.time)
+            ),
+            _ => "No scans recorded yet. Each completed scan is added here.".to_string(),
+        };
+        let summary = Paragraph::new(summary)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(summary, chunks[0]);
 
-        let header = [
-            "Package Name",
-            "Type",
-            "Last Accessed",
-            "Last Accessed Path",
-        ]
-        .into_iter()
-        .map(Cell::from)
-        .collect::<Row>()
-        .style(header_style)
-        .height(1);
+        // Only the most recent scans that fit are drawn
+        let width = chunks[1].width.saturating_sub(2) as usize;
+        let recent = &self.history[self.history.len().saturating_sub(width)..];
 
-        let rows = self.items.iter().enumerate().map(|(i, package)| {
-            let color = match i % 2 {
-                0 => self.colors.normal_row_color,
-                _ => self.colors.alt_row_color,
-            };
-            let item = package.get_display_fields();
-            item.into_iter()
-                .map(|content| Cell::from(Text::from(format!("\n {content} \n"))))
-                .collect::<Row>()
-                .style(Style::new().fg(self.colors.row_fg).bg(color))
-                .height(4)
-        });
+        let counts: Vec<u64> = recent.iter().map(|entry| entry.packages as u64).collect();
+        let counts = Sparkline::default()
+            .block(Block::bordered().title("Packages"))
+            .data(&counts)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(counts, chunks[1]);
 
-        let bar = " █ ";
+        let sizes: Vec<Option<u64>> = recent.iter().map(|entry| entry.total_bytes).collect();
+        let sizes = Sparkline::default()
+            .block(Block::bordered().title("Total size"))
+            .data(sizes)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(sizes, chunks[2]);
 
-        let t = Table::new(
-            rows,
-            [
-                Constraint::Length(self.longest_item_lens.0 + 10),
-                Constraint::Min(self.longest_item_lens.1 + 3),
-                Constraint::Min(self.longest_item_lens.2),
-                Constraint::Min(self.longest_item_lens.3),
-            ],
-        )
-        .header(header)
-        .row_highlight_style(selected_row_style)
-        .column_highlight_style(selected_col_style)
-        .cell_highlight_style(selected_cell_style)
-        .highlight_symbol(Text::from(vec![
-            "".into(),
-            bar.into(),
-            bar.into(),
-            "".into(),
-        ]))
-        .bg(self.colors.buffer_bg)
-        .highlight_spacing(HighlightSpacing::Always);
+        let entries: Vec<Line> = self
+            .history
+            .iter()
+            .rev()
+            .map(|entry| {
+                Line::from(format!(
+                    "{}  {:>5} packages  {:>10}",
+                    format_timestamp(entry.time),
+                    entry.packages,
+                    entry
+                        .total_bytes
+                        .map(format_size)
+                        .unwrap_or_else(|| "-".to_string())
+                ))
+            })
+            .collect();
+        let entries = Paragraph::new(entries)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(entries, chunks[3]);
 
-        frame.render_stateful_widget(t, area, &mut self.state);
+        let controls = Paragraph::new("[n/Space] Back")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[4]);
     }
 
-    fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
-        frame.render_stateful_widget(
-            Scrollbar::default()
-                .orientation(ScrollbarOrientation::VerticalRight)
-                .begin_symbol(None)
-                .end_symbol(None),
-            area.inner(Margin {
-                vertical: 1,
-                horizontal: 1,
-            }),
-            &mut self.scroll_state,
-        );
-    }
+    fn render_broken_links(&self, frame: &mut Frame) {
+        let links_block = Block::default()
+            .title(format!("{} Broken Links", self.icon("🔗", "[links]")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.colors.footer_border_color))
+            .style(Style::default().bg(self.colors.buffer_bg));
 
-    fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        let info_footer = Paragraph::new(Text::from_iter(INFO_TEXT))
-            .style(
-                Style::new()
-                    .fg(self.colors.row_fg)
-                    .bg(self.colors.buffer_bg),
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2), // Summary
+                Constraint::Min(3),    // Links
+                Constraint::Length(1), // Controls
+            ])
+            .split(links_block.inner(frame.area()));
+
+        frame.render_widget(links_block, frame.area());
+
+        let summary = if self.broken_links.is_empty() {
+            "No broken symlinks found in the Homebrew bin directory.".to_string()
+        } else {
+            format!(
+                "{} symlink{} whose target no longer exists, usually from a removed version.",
+                self.broken_links.len(),
+                if self.broken_links.len() == 1 {
+                    ""
+                } else {
+                    "s"
+                }
             )
-            .centered()
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Double)
-                    .border_style(Style::new().fg(self.colors.footer_border_color)),
-            );
-        frame.render_widget(info_footer, area);
+        };
+        let summary = Paragraph::new(summary)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(summary, chunks[0]);
+
+        let links: Vec<Line> = self
+            .broken_links
+            .iter()
+            .map(|link| {
+                Line::from(format!(
+                    "{} -> {}",
+                    link.path.display(),
+                    link.target.display()
+                ))
+            })
+            .collect();
+        let links = Paragraph::new(links).style(Style::default().fg(Color::Yellow));
+        frame.render_widget(links, chunks[1]);
+
+        let controls = if self.broken_links.is_empty() {
+            "[n/Space] Back"
+        } else {
+            "[c] Remove with brew cleanup --prune-prefix  [n/Space] Back"
+        };
+        let controls = Paragraph::new(controls)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[2]);
     }
 
-    fn render_package_details(&self, frame: &mut Frame, package_index: usize) {
-        if package_index >= self.items.len() {
-            return;
-        }
+    fn render_confirm_maintenance(&self, frame: &mut Frame, action: &MaintenanceAction) {
+        let title = format!("Confirm {}", action.label());
+        let back = "[n/Space] Back";
+        let restoring = matches!(action, MaintenanceAction::RestoreBrewfile(_));
 
-        let package = &self.items[package_index];
+        match self.maintenance_preview {
+            None => self.render_confirm(
+                frame,
+                &title,
+                format!("Checking what {} would remove...", action.label()),
+                Vec::new(),
+                back,
+            ),
+            Some(Err(ref e)) => self.render_confirm(
+                frame,
+                &title,
+                format!("Dry run failed: {}", e),
+                Vec::new(),
+                back,
+            ),
+            Some(Ok(ref preview)) if preview.items.is_empty() => self.render_confirm(
+                frame,
+                &title,
+                if restoring {
+                    "Everything in the Brewfile is already installed.".to_string()
+                } else {
+                    "Nothing to remove.".to_string()
+                },
+                Vec::new(),
+                back,
+            ),
+            Some(Ok(ref preview)) => {
+                let autoremove = *action == MaintenanceAction::Autoremove;
+                let (scanned, known) = if autoremove {
+                    self.scanned_sizes(&preview.items)
+                } else {
+                    (0, 0)
+                };
+                let freed = if known > 0 {
+                    let unknown = preview.items.len() - known;
+                    format!(
+                        ", freeing {} by scanned sizes{}",
+                        format_size(scanned),
+                        if unknown > 0 {
+                            format!(" ({} not sized)", unknown)
+                        } else {
+                            String::new()
+                        }
+                    )
+                } else {
+                    preview
+                        .freed
+                        .as_ref()
+                        .map(|size| format!(", freeing approximately {}", size))
+                        .unwrap_or_default()
+                };
+                let plural = if preview.items.len() == 1 { "" } else { "s" };
+                let warning = if restoring {
+                    format!(
+                        "`{}` will install {} missing package{}.\n\nThis may take a long time.",
+                        action.command(),
+                        preview.items.len(),
+                        plural
+                    )
+                } else {
+                    format!(
+                        "{} will remove {} item{}{}.\n\nThis action cannot be undone!",
+                        action.label(),
+                        preview.items.len(),
+                        plural,
+                        freed
+                    )
+                };
+                let items = preview
+                    .items
+                    .iter()
+                    .map(|item| {
+                        let size = self
+                            .items
+                            .iter()
+                            .find(|p| autoremove && p.name == *item)
+                            .and_then(|p| p.size_bytes);
+                        match size {
+                            Some(size) => Line::from(format!("{} ({})", item, format_size(size))),
+                            None => Line::from(item.as_str()),
+                        }
+                    })
+                    .collect();
+                self.render_confirm(
+                    frame,
+                    &title,
+                    warning,
+                    items,
+                    "[y/Enter] Run  [n/Space] Cancel",
+                );
+            }
+        }
+    }
 
-        let details_block = Block::default()
-            .title(format!("📦 Package Details: {}", package.name))
+    fn render_select_by_age(&self, frame: &mut Frame, input: &str) {
+        let prompt_block = Block::default()
+            .title(format!("{} Select by Age", self.icon("📅", "[age]")))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(self.colors.footer_border_color))
             .style(Style::default().bg(self.colors.buffer_bg));
@@ -837,107 +5462,125 @@ impl App {
             .direction(ratatui::layout::Direction::Vertical)
             .margin(2)
             .constraints([
-                Constraint::Length(2), // Name and type
-                Constraint::Length(2), // Last accessed
-                Constraint::Length(2), // Path
+                Constraint::Length(2), // Prompt
+                Constraint::Length(1), // Input
                 Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Match preview
+                Constraint::Min(0),
                 Constraint::Length(1), // Controls
             ])
-            .split(details_block.inner(frame.area()));
+            .split(prompt_block.inner(frame.area()));
 
-        frame.render_widget(details_block, frame.area());
+        frame.render_widget(prompt_block, frame.area());
 
-        // Package name and type
-        let name_type = Paragraph::new(format!(
-            "Name: {}\nType: {}",
-            package.name,
-            package.package_type()
-        ))
-        .style(Style::default().fg(self.colors.row_fg));
-        frame.render_widget(name_type, chunks[0]);
+        let prompt =
+            Paragraph::new("Mark every visible package not accessed in more than this many days:")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(self.colors.row_fg));
+        frame.render_widget(prompt, chunks[0]);
 
-        // Last accessed
-        let accessed = Paragraph::new(format!("Last Accessed: {}", package.format_last_accessed()))
+        let field = Paragraph::new(format!("{}_ days", input))
+            .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Yellow));
-        frame.render_widget(accessed, chunks[1]);
+        frame.render_widget(field, chunks[1]);
 
-        // Path
-        let path = Paragraph::new(format!(
-            "Path: {}",
-            package.last_accessed_path.as_deref().unwrap_or("Unknown")
-        ))
-        .style(Style::default().fg(Color::Cyan));
-        frame.render_widget(path, chunks[2]);
+        if let Ok(days) = input.parse() {
+            let (matches, kept) = self.packages_older_than(days);
+            let preview = Paragraph::new(if kept > 0 {
+                format!("{} packages match, {} kept skipped", matches.len(), kept)
+            } else {
+                format!("{} packages match", matches.len())
+            })
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Cyan));
+            frame.render_widget(preview, chunks[3]);
+        }
 
-        // Controls
-        let controls = Paragraph::new("[Enter/Space] Back  [d] Delete  [ESC] Quit")
+        let controls = Paragraph::new("[Enter] Mark  [Backspace] Edit  [Esc] Cancel")
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Gray));
-        frame.render_widget(controls, chunks[4]);
+        frame.render_widget(controls, chunks[5]);
     }
 
-    fn render_confirm_delete(&self, frame: &mut Frame, package_index: usize) {
-        if package_index >= self.items.len() {
-            return;
-        }
-
-        let package = &self.items[package_index];
-
-        let confirm_block = Block::default()
-            .title("⚠️  Confirm Delete")
+    fn render_size_filter(&self, frame: &mut Frame, input: &str) {
+        let prompt_block = Block::default()
+            .title(format!("{} Filter by Size", self.icon("📏", "[size]")))
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Red))
+            .border_style(Style::default().fg(self.colors.footer_border_color))
             .style(Style::default().bg(self.colors.buffer_bg));
 
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .margin(2)
             .constraints([
-                Constraint::Length(3), // Warning message
-                Constraint::Length(2), // Package info
+                Constraint::Length(2), // Prompt
+                Constraint::Length(1), // Input
                 Constraint::Length(1), // Empty space
+                Constraint::Length(1), // Match preview or error
+                Constraint::Min(0),
                 Constraint::Length(1), // Controls
             ])
-            .split(confirm_block.inner(frame.area()));
-
-        frame.render_widget(confirm_block, frame.area());
+            .split(prompt_block.inner(frame.area()));
 
-        // Warning message
-        let warning = Paragraph::new(format!(
-            "Are you sure you want to delete '{}'?\n\nThis action cannot be undone!",
-            package.name
-        ))
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Red));
-        frame.render_widget(warning, chunks[0]);
+        frame.render_widget(prompt_block, frame.area());
 
-        // Package info
-        let info = Paragraph::new(format!(
-            "Type: {}\nPath: {}",
-            package.package_type(),
-            package.last_accessed_path.as_deref().unwrap_or("Unknown")
-        ))
+        let prompt = Paragraph::new(
+            "Show only packages in a size range, e.g. >100MB, <1GB or 10MB-1GB. Leave empty to clear:",
+        )
         .alignment(Alignment::Center)
         .style(Style::default().fg(self.colors.row_fg));
-        frame.render_widget(info, chunks[1]);
+        frame.render_widget(prompt, chunks[0]);
 
-        // Controls
-        let controls =
-            Paragraph::new("[y] Yes, Delete  [n] No, Cancel  [Enter] Delete  [Space] Cancel")
-                .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::Gray));
-        frame.render_widget(controls, chunks[3]);
-    }
+        let field = Paragraph::new(format!("{}_", input))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(field, chunks[1]);
 
-    fn render_deleting(&self, frame: &mut Frame, package_index: usize) {
-        if package_index >= self.items.len() {
-            return;
+        let preview = match self.size_filter_error {
+            Some(ref e) => Some(Paragraph::new(e.as_str()).style(Style::default().fg(Color::Red))),
+            None => SizeRange::parse(input).ok().map(|range| {
+                let matching: Vec<u64> = self
+                    .items
+                    .iter()
+                    .filter_map(|p| p.size_bytes)
+                    .filter(|&size| range.contains(size))
+                    .collect();
+                Paragraph::new(format!(
+                    "{} packages match, {} in total",
+                    matching.len(),
+                    format_size(matching.iter().sum())
+                ))
+                .style(Style::default().fg(Color::Cyan))
+            }),
+        };
+        if let Some(preview) = preview {
+            frame.render_widget(preview.alignment(Alignment::Center), chunks[3]);
         }
 
-        let package = &self.items[package_index];
+        let controls = Paragraph::new("[Enter] Apply  [Backspace] Edit  [Esc] Cancel")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[5]);
+    }
 
-        let deleting_block = Block::default()
-            .title("🗑️  Uninstalling Package")
+    fn render_batch_deleting(&self, frame: &mut Frame) {
+        let running = self.batch_running();
+        let title = if running {
+            format!(
+                "{} Uninstalling Packages{}",
+                self.icon("🗑️ ", "[del]"),
+                self.dry_run_tag()
+            )
+        } else {
+            format!(
+                "{} Batch Uninstall Finished{}",
+                self.icon("✅", "[ok]"),
+                self.dry_run_tag()
+            )
+        };
+
+        let batch_block = Block::default()
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Yellow))
             .style(Style::default().bg(self.colors.buffer_bg));
@@ -946,61 +5589,251 @@ impl App {
             .direction(ratatui::layout::Direction::Vertical)
             .margin(1)
             .constraints([
-                Constraint::Length(1), // Package info
-                Constraint::Length(1), // Empty line
-                Constraint::Min(5),    // Command output
-                Constraint::Length(1), // Controls
+                Constraint::Percentage(40), // Per-package status
+                Constraint::Min(5),         // Command output
+                Constraint::Length(1),      // Controls
             ])
-            .split(deleting_block.inner(frame.area()));
+            .split(batch_block.inner(frame.area()));
 
-        frame.render_widget(deleting_block, frame.area());
+        frame.render_widget(batch_block, frame.area());
 
-        // Package info
-        let package_info = Paragraph::new(format!(
-            "Uninstalling: {} ({})",
-            package.name,
-            package.package_type()
-        ))
-        .style(Style::default().fg(Color::Yellow));
-        frame.render_widget(package_info, chunks[0]);
+        let ascii = self.config.ascii_mode;
+        let mut indices: Vec<usize> = self.batch_status.keys().copied().collect();
+        indices.sort_unstable();
+        let lines: Vec<Line> = indices
+            .iter()
+            .filter_map(|i| Some((self.items.get(*i)?, &self.batch_status[i])))
+            .map(|(package, status)| {
+                let mut spans = vec![
+                    Span::styled(status.glyph(ascii), Style::default().fg(status.color())),
+                    Span::raw(format!(" {}", package.name)),
+                ];
+                if let BatchStatus::Failed(ref error) = status {
+                    spans.push(Span::styled(
+                        format!("  {}", error),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        let done = self
+            .batch_status
+            .values()
+            .filter(|status| matches!(status, BatchStatus::Done | BatchStatus::Failed(_)))
+            .count();
+        let status_list = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!("Packages ({}/{})", done, self.batch_status.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+        frame.render_widget(status_list, chunks[0]);
 
-        // Command output
-        let output_text = if self.delete_output.is_empty() {
-            "Starting uninstall process...".to_string()
+        self.render_command_output(frame, chunks[1], "");
+
+        let controls = if running {
+            "Uninstalling...  [q] Force Quit"
         } else {
-            self.delete_output.join("\n")
+            "[Enter/Space/ESC] Back to table  [q] Quit"
         };
+        let controls = Paragraph::new(controls)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
+        frame.render_widget(controls, chunks[2]);
+    }
+}
 
-        let output_block = Block::default()
-            .title("Command Output")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan));
+/// How long ago `time` was, e.g. "3 days ago".
+fn format_elapsed(time: SystemTime) -> String {
+    match time.elapsed() {
+        Ok(duration) => {
+            let secs = duration.as_secs();
 
-        let output_paragraph = Paragraph::new(output_text)
-            .block(output_block)
-            .style(Style::default().fg(Color::Green))
-            .scroll((
-                if self.delete_output.len() > 10 {
-                    self.delete_output.len().saturating_sub(10) as u16
-                } else {
-                    0
-                },
-                0,
-            ));
+            if secs < 60 {
+                "Just now".to_string()
+            } else if secs < 3600 {
+                let mins = secs / 60;
+                format!("{} min{} ago", mins, if mins == 1 { "" } else { "s" })
+            } else if secs < 86400 {
+                let hours = secs / 3600;
+                format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
+            } else if secs < 2592000 {
+                // 30 days
+                let days = secs / 86400;
+                format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
+            } else if secs < 31536000 {
+                // 365 days
+                let months = secs / 2592000;
+                format!("{} month{} ago", months, if months == 1 { "" } else { "s" })
+            } else {
+                let years = secs / 31536000;
+                format!("{} year{} ago", years, if years == 1 { "" } else { "s" })
+            }
+        }
+        // Up to a day ahead is clock drift between the filesystem and us
+        Err(ahead) if ahead.duration() <= Duration::from_secs(86400) => "Just now".to_string(),
+        // Further in the future the time can't be trusted; show the date
+        Err(_) => match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since) => format_timestamp(since.as_secs()),
+            Err(_) => "Unknown date".to_string(),
+        },
+    }
+}
 
-        frame.render_widget(output_paragraph, chunks[2]);
+/// Local date and time of a unix timestamp, e.g. "2024-06-10 14:03".
+fn format_timestamp(secs: u64) -> String {
+    format_local(secs, "%Y-%m-%d %H:%M")
+}
 
-        // Controls
-        let controls = Paragraph::new("[c] Stop Watching  [ESC] Force Quit")
-            .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::Gray));
-        frame.render_widget(controls, chunks[3]);
+/// A relative time with the local date after it, e.g. "3 days ago (2024-03-12)".
+fn format_relative_with_date(relative: &str, time: SystemTime) -> String {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => format!(
+            "{} ({})",
+            relative,
+            format_local(since.as_secs(), "%Y-%m-%d")
+        ),
+        Err(_) => relative.to_string(),
+    }
+}
+
+fn format_local(secs: u64, format: &str) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format(format)
+                .to_string()
+        })
+        .unwrap_or_else(|| "Unknown date".to_string())
+}
+
+/// Parses a size like "100MB", "1.5 GB" or "512k" into bytes, counting in
+/// powers of 1024 like [`format_size`].
+fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a size", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        "T" => 1024 * 1024 * 1024 * 1024,
+        _ => {
+            return Err(format!(
+                "Unknown unit in '{}', use B, KB, MB, GB or TB",
+                input
+            ))
+        }
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Renders a byte count as a human-readable size, e.g. `4.2 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Whether the walked size and brew's figure differ by more than 10% (and 1 MB).
+fn sizes_differ(on_disk: Option<u64>, reported: u64) -> bool {
+    let Some(on_disk) = on_disk else {
+        return false;
+    };
+    let diff = on_disk.abs_diff(reported);
+    diff > 1024 * 1024 && diff * 10 > on_disk.max(reported)
+}
+
+/// Pipes `text` into the first clipboard tool that is available.
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    const TOOLS: [(&str, &[&str]); 4] = [
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+
+    for (tool, args) in TOOLS {
+        let Ok(mut child) = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("Failed to write to {}: {}", tool, e))?;
+        }
+        let status = child
+            .wait()
+            .map_err(|e| format!("Failed to wait for {}: {}", tool, e))?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(format!("{} exited with code: {:?}", tool, status.code()))
+        };
+    }
+
+    Err("No clipboard tool found (tried pbcopy, wl-copy, xclip, xsel)".to_string())
+}
+
+/// Trims a package path to the part after the Homebrew prefix, e.g.
+/// `Cellar/wget/1.24.5`, or to its file name when it's outside the prefix.
+fn short_path(path: &str) -> &str {
+    ["/Cellar/", "/Caskroom/", "/opt/"]
+        .iter()
+        .find_map(|marker| path.rfind(marker).map(|i| &path[i + 1..]))
+        .or_else(|| path.rsplit('/').find(|part| !part.is_empty()))
+        .unwrap_or(path)
+}
+
+/// Number of formulae and casks in `items`.
+fn type_counts(items: &[Package]) -> (usize, usize) {
+    let formulae = items
+        .iter()
+        .filter(|package| package.package_type == PackageType::Formula)
+        .count();
+    (formulae, items.len() - formulae)
+}
+
+/// Row color for how long a package has gone unused: green under 30 days,
+/// yellow up to 180, red beyond that or when never accessed.
+fn staleness_color(package: &Package) -> Color {
+    if !package.idle_longer_than(30) {
+        Color::Green
+    } else if !package.idle_longer_than(180) {
+        Color::Yellow
+    } else {
+        Color::Red
     }
 }
 
-fn constraint_len_calculator(items: &[Package]) -> (u16, u16, u16, u16) {
+fn constraint_len_calculator(items: &[Package]) -> (u16, u16, u16, u16, u16) {
     if items.is_empty() {
-        return (20, 10, 15, 20);
+        return (20, 10, 15, 20, 8);
     }
 
     let name_len = items
@@ -1026,8 +5859,17 @@ fn constraint_len_calculator(items: &[Package]) -> (u16, u16, u16, u16) {
 
     let last_accessed_time_len = items
         .iter()
-        .map(|package| package.last_accessed())
-        .map(|s| s.width())
+        // Room for the " ≈" shown after modification times
+        .map(|package| package.format_last_accessed().width() + 2)
+        .max()
+        .unwrap_or(0);
+
+    // At least as wide as the "Version" header
+    let version_len = items
+        .iter()
+        .filter_map(|package| package.version.as_deref())
+        .map(UnicodeWidthStr::width)
+        .chain(["Version".len()])
         .max()
         .unwrap_or(0);
 
@@ -1036,5 +5878,273 @@ fn constraint_len_calculator(items: &[Package]) -> (u16, u16, u16, u16) {
         type_len as u16,
         last_accessed_path_len as u16,
         last_accessed_time_len as u16,
+        version_len as u16,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, size_bytes: u64) -> Package {
+        Package {
+            name: name.to_string(),
+            package_type: PackageType::Formula,
+            version: None,
+            last_accessed: None,
+            time_source: TimeSource::default(),
+            last_accessed_path: None,
+            installed_at: None,
+            paths: Vec::new(),
+            size_bytes: Some(size_bytes),
+            metadata: None,
+            orphans: None,
+            dependents: Vec::new(),
+            outdated: false,
+        }
+    }
+
+    /// Three packages sorted largest first: c (300), a (200), b (100).
+    fn app_sorted_by_size() -> App {
+        let mut app = App::new(AppConfig::default());
+        app.items = vec![package("a", 200), package("b", 100), package("c", 300)];
+        app.sort_mode = SortMode::Size;
+        app.sort_packages();
+        app
+    }
+
+    fn visible_names(app: &App) -> Vec<&str> {
+        app.filtered_indices
+            .iter()
+            .map(|&i| app.items[i].name.as_str())
+            .collect()
+    }
+
+    fn selected_name(app: &App) -> Option<&str> {
+        let row = app.state.selected()?;
+        Some(app.items[app.filtered_indices[row]].name.as_str())
+    }
+
+    #[test]
+    fn refresh_updates_the_row_in_place() {
+        let mut app = app_sorted_by_size();
+        app.state.select(Some(1));
+
+        // "a" shrinks below "b", but the table isn't re-sorted under the cursor
+        let mut fresh = package("a", 10);
+        fresh.version = Some("2.0".to_string());
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Ok(fresh)).unwrap();
+        app.refresh_receiver = Some(receiver);
+        app.check_package_refresh();
+
+        assert_eq!(visible_names(&app), ["c", "a", "b"]);
+        assert_eq!(selected_name(&app), Some("a"));
+        let a = &app.items[app.filtered_indices[1]];
+        assert_eq!(a.size_bytes, Some(10));
+        assert_eq!(a.version.as_deref(), Some("2.0"));
+    }
+
+    #[test]
+    fn refresh_turns_off_watching() {
+        let mut app = app_sorted_by_size();
+        app.watching_since = Some(Instant::now());
+        app.refresh_package_at(0);
+        assert!(app.watching_since.is_none());
+        assert!(app.watch_receiver.is_none());
+    }
+
+    #[test]
+    fn paging_moves_a_screenful_and_stops_at_the_ends() {
+        let mut app = App::new(AppConfig::default());
+        app.items = (0..10).map(|i| package(&format!("p{}", i), 100)).collect();
+        app.apply_filter();
+        // Room for exactly three rows
+        app.terminal_size = (80, FOOTER_HEIGHT + 3 + 3 * app.item_height() as u16);
+        app.first_row();
+
+        app.page_down();
+        assert_eq!(app.state.selected(), Some(3));
+        app.last_row();
+        assert_eq!(app.state.selected(), Some(9));
+        app.page_down();
+        assert_eq!(app.state.selected(), Some(9));
+        app.page_up();
+        assert_eq!(app.state.selected(), Some(6));
+        app.first_row();
+        app.page_up();
+        assert_eq!(app.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn scrolled_up_output_stays_put_as_lines_arrive() {
+        let mut app = App::new(AppConfig::default());
+        app.terminal_size = (80, 20);
+        let height = app.output_viewport_height();
+        let (sender, receiver) = mpsc::channel();
+        app.delete_output_receiver = Some(receiver);
+        for i in 0..50 {
+            sender.send(format!("line {}", i)).unwrap();
+        }
+        app.drain_delete_output();
+        assert_eq!(app.delete_output.len(), 50);
+        assert_eq!(app.output_scroll_back, 0);
+
+        app.scroll_output(-5);
+        sender.send("line 50".to_string()).unwrap();
+        app.drain_delete_output();
+        assert_eq!(app.output_scroll_back, 6);
+
+        app.scroll_output(-1000);
+        assert_eq!(app.output_scroll_back, 51 - height);
+        app.scroll_output(1000);
+        assert_eq!(app.output_scroll_back, 0);
+    }
+
+    #[test]
+    fn title_counts_types_and_what_a_filter_hides() {
+        let mut app = app_sorted_by_size();
+        app.items[0].package_type = PackageType::Cask;
+        assert_eq!(
+            app.table_title(),
+            "Homebrew Packages — 3 total (2 formulae, 1 cask)"
+        );
+
+        app.filter = "a".to_string();
+        app.apply_filter();
+        assert_eq!(
+            app.table_title(),
+            "Homebrew Packages — 1 of 3 shown (2 formulae, 1 cask)"
+        );
+    }
+
+    #[test]
+    fn top_sizes_jumps_to_the_package_in_the_table() {
+        let mut app = app_sorted_by_size();
+        app.sort_mode = SortMode::Name;
+        app.sort_packages();
+        app.apply_filter();
+        let top: Vec<&str> = app
+            .top_sizes()
+            .iter()
+            .map(|&i| app.items[i].name.as_str())
+            .collect();
+        assert_eq!(top, ["c", "a", "b"]);
+
+        app.open_screen(AppState::TopSizes(0));
+        app.move_top_sizes(5);
+        assert!(matches!(app.app_state, AppState::TopSizes(2)));
+        app.jump_to_top_size(0);
+        assert!(matches!(app.app_state, AppState::Table));
+        assert_eq!(selected_name(&app), Some("c"));
+    }
+
+    #[test]
+    fn quitting_asks_first_only_while_something_runs() {
+        let mut app = App::new(AppConfig::default());
+        assert_eq!(app.running_operation(&AppState::Table), None);
+        assert!(app
+            .running_operation(&AppState::RunningMaintenance(MaintenanceAction::Cleanup))
+            .is_some());
+
+        app.background_delete = Some("wget".to_string());
+        assert_eq!(
+            app.running_operation(&AppState::Table).as_deref(),
+            Some("'wget' is still uninstalling in the background")
+        );
+    }
+
+    #[test]
+    fn delete_keeps_cursor_on_the_same_row() {
+        let mut app = app_sorted_by_size();
+        assert_eq!(visible_names(&app), ["c", "a", "b"]);
+
+        app.state.select(Some(1));
+        app.handle_delete_result(1, true, "Deleted a".to_string());
+
+        assert_eq!(visible_names(&app), ["c", "b"]);
+        assert_eq!(selected_name(&app), Some("b"));
+    }
+
+    #[test]
+    fn delete_of_last_row_selects_the_new_last_row() {
+        let mut app = app_sorted_by_size();
+
+        app.state.select(Some(2));
+        app.handle_delete_result(2, true, "Deleted b".to_string());
+
+        assert_eq!(visible_names(&app), ["c", "a"]);
+        assert_eq!(app.state.selected(), Some(1));
+        assert_eq!(selected_name(&app), Some("a"));
+    }
+
+    #[test]
+    fn delete_under_a_filter_uses_the_visible_row() {
+        let mut app = app_sorted_by_size();
+        app.items.push(package("ab", 50));
+        app.sort_packages();
+        app.filter = "a".to_string();
+        app.apply_filter();
+        assert_eq!(visible_names(&app), ["a", "ab"]);
+
+        // "a" is the second package overall but the first visible row
+        let index = app.filtered_indices[0];
+        app.state.select(Some(0));
+        app.handle_delete_result(index, true, "Deleted a".to_string());
+
+        assert_eq!(visible_names(&app), ["ab"]);
+        assert_eq!(selected_name(&app), Some("ab"));
+    }
+
+    #[test]
+    fn delete_keeps_marks_on_the_same_packages() {
+        let mut app = app_sorted_by_size();
+        // Mark "b", the row below the one being deleted
+        app.marked.insert(2);
+
+        app.handle_delete_result(1, true, "Deleted a".to_string());
+
+        let marked: Vec<&str> = app
+            .marked
+            .iter()
+            .map(|&i| app.items[i].name.as_str())
+            .collect();
+        assert_eq!(marked, ["b"]);
+    }
+
+    #[test]
+    fn every_help_section_is_reachable_at_80x24() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut app = App::new(AppConfig::default());
+        app.terminal_size = (80, 24);
+        app.show_help = true;
+
+        let mut screens = Vec::new();
+        for _ in 0..10 {
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+            let buffer = terminal.backend().buffer();
+            screens.push(
+                buffer
+                    .content()
+                    .iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>(),
+            );
+            app.handle_help_key(KeyCode::PageDown);
+        }
+        assert!(app.show_help);
+        assert!(screens[0].contains("more…"));
+
+        let seen = screens.concat();
+        for (title, _) in HELP_SECTIONS {
+            assert!(seen.contains(title), "{} never shown", title);
+        }
+        assert!(seen.contains("Marks"));
+
+        app.handle_help_key(KeyCode::Char('x'));
+        assert!(!app.show_help);
+        assert_eq!(app.help_page, 0);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::Package;
+
+static LOG: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+
+/// Appends every package found by subsequent scans to `path`, one JSON object
+/// per line.
+pub fn init(path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    *LOG.lock().unwrap() = Some(BufWriter::new(file));
+    Ok(())
+}
+
+/// Records one scanned package, if a scan log is open. Flushed right away so
+/// an interrupted scan still leaves everything found so far.
+pub fn record(package: &Package) {
+    if let Some(ref mut log) = *LOG.lock().unwrap() {
+        if serde_json::to_writer(&mut *log, package).is_ok() {
+            let _ = writeln!(log);
+        }
+        let _ = log.flush();
+    }
+}
+
+/// Flushes and closes the scan log.
+pub fn close() {
+    if let Some(mut log) = LOG.lock().unwrap().take() {
+        let _ = log.flush();
+    }
+}
@@ -1,16 +1,438 @@
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, ChildStderr, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
-use std::time::{Duration, Instant, SystemTime};
-use std::{fs, thread};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{fmt, fs, thread};
 
-use crate::{Package, PackageType};
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{command_log, scan_log};
+use crate::{Package, PackageMetadata, PackageType, TimeSource};
+
+/// When set, scans read packages from this JSON file instead of running `brew`.
+///
+/// Meant for demos, screenshots and exercising the TUI without Homebrew.
+pub const FIXTURES_ENV: &str = "BREWSWEEP_FIXTURES";
+
+/// A dangling symlink and the path it points to.
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    pub path: PathBuf,
+    pub target: PathBuf,
+}
 
 pub struct HomebrewScanner {
     pub state: Arc<Mutex<ScanningState>>,
     pub packages: Arc<Mutex<Vec<Package>>>,
+    /// Dangling symlinks in `<prefix>/bin` found by the last scan.
+    pub broken_links: Arc<Mutex<Vec<BrokenLink>>>,
+    pub options: ScanOptions,
+    /// Where `brew` commands go; the real `brew` unless built with `with_backend`.
+    backend: Arc<dyn BrewBackend>,
+}
+
+/// The `brew` commands scans and uninstalls depend on, so they can run
+/// without a real Homebrew install.
+pub trait BrewBackend: Send + Sync {
+    /// The Homebrew prefix, e.g. `/opt/homebrew`.
+    fn prefix(&self) -> Result<PathBuf, ScanError>;
+    /// Names of the installed formulae, from `brew list --formula`.
+    fn list_formulas(&self) -> Result<Vec<String>, ScanError>;
+    /// Names of the installed casks, from `brew list --cask`.
+    fn list_casks(&self) -> Result<Vec<String>, ScanError>;
+    /// Metadata for every installed package, from `brew info --json=v2`.
+    fn info(&self) -> Result<PackageInfoReport, String>;
+    /// Metadata for just `package`; `None` if brew's entry couldn't be parsed.
+    fn info_for(&self, package: &Package) -> Result<Option<PackageInfo>, String>;
+    /// Each installed formula's dependencies, from `brew deps --installed`.
+    fn dependencies(&self) -> Result<HashMap<String, Vec<String>>, String>;
+    /// Installed packages with a newer version available, from `brew outdated`.
+    fn outdated(&self) -> Result<Vec<(String, PackageType)>, String>;
+    /// Starts `brew <args>` uninstalling `package`, with stdout and stderr piped.
+    fn uninstall(&self, package: &Package, args: &[&str]) -> io::Result<Child>;
+}
+
+/// Runs the real `brew` from `PATH`.
+pub struct BrewCli;
+
+impl BrewBackend for BrewCli {
+    fn prefix(&self) -> Result<PathBuf, ScanError> {
+        HomebrewScanner::get_homebrew_prefix()
+    }
+
+    fn list_formulas(&self) -> Result<Vec<String>, ScanError> {
+        HomebrewScanner::list_installed("--formula")
+    }
+
+    fn list_casks(&self) -> Result<Vec<String>, ScanError> {
+        HomebrewScanner::list_installed("--cask")
+    }
+
+    fn info(&self) -> Result<PackageInfoReport, String> {
+        HomebrewScanner::fetch_package_info()
+    }
+
+    fn info_for(&self, package: &Package) -> Result<Option<PackageInfo>, String> {
+        HomebrewScanner::fetch_one_package_info(package)
+    }
+
+    fn dependencies(&self) -> Result<HashMap<String, Vec<String>>, String> {
+        HomebrewScanner::fetch_dependencies()
+    }
+
+    fn outdated(&self) -> Result<Vec<(String, PackageType)>, String> {
+        HomebrewScanner::fetch_outdated()
+    }
+
+    fn uninstall(&self, package: &Package, args: &[&str]) -> io::Result<Child> {
+        let mut command = Command::new("brew");
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // Some casks run privileged uninstall steps through sudo, which would try to
+        // prompt on the TUI's terminal. Homebrew passes `-A` to sudo when SUDO_ASKPASS
+        // is set, so point it at a helper that fails and let sudo error out instead.
+        if package.package_type == PackageType::Cask && std::env::var_os("SUDO_ASKPASS").is_none() {
+            command.env("SUDO_ASKPASS", "/usr/bin/false");
+        }
+
+        command.spawn()
+    }
+}
+
+/// Which kinds of packages a scan should include.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanScope {
+    #[default]
+    All,
+    Formulae,
+    Casks,
+}
+
+impl ScanScope {
+    pub fn includes_formulae(self) -> bool {
+        self != ScanScope::Casks
+    }
+
+    pub fn includes_casks(self) -> bool {
+        self != ScanScope::Formulae
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub scope: ScanScope,
+    /// Extra locations whose access times count as cask usage.
+    pub cask_data_dirs: Vec<String>,
+    /// Only read the access time of one representative path per package,
+    /// skipping directory enumeration and size calculation.
+    pub fast: bool,
+}
+
+/// Extra flags for `brew uninstall`.
+#[derive(Debug, Clone, Default)]
+pub struct UninstallOptions {
+    /// Pass `--force`, removing all installed versions.
+    pub force: bool,
+    /// Pass `--zap` for casks, also removing the app's preferences and
+    /// support files. Ignored for formulae.
+    pub zap: bool,
+    /// Only list what would be removed. `brew uninstall` has no dry run of
+    /// its own, so brew isn't run at all.
+    pub dry_run: bool,
+    /// Lets another thread stop the uninstall midway.
+    pub cancel: CancelHandle,
+}
+
+/// Shared slot for a running `brew` child so the UI can kill it.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<Mutex<Option<Child>>>);
+
+impl CancelHandle {
+    /// Kills the child if it is still running. Returns false when there was
+    /// nothing left to kill, i.e. the command has already finished.
+    pub fn cancel(&self) -> bool {
+        match self.0.lock().unwrap().as_mut() {
+            Some(child) => child.kill().is_ok(),
+            None => false,
+        }
+    }
+
+    fn attach(&self, child: Child) {
+        *self.0.lock().unwrap() = Some(child);
+    }
+
+    fn detach(&self) -> Option<Child> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Metadata for one installed package from `brew info --json=v2`.
+#[derive(Debug, Clone)]
+pub struct PackageInfo {
+    pub name: String,
+    pub package_type: PackageType,
+    pub metadata: PackageMetadata,
+    pub installed_at: Option<SystemTime>,
+    /// Installed versions: every keg for formulae, newest first; the one
+    /// installed version for casks.
+    pub versions: Vec<String>,
+    /// App bundles a cask installs, e.g. "Firefox.app". Empty for formulae.
+    pub apps: Vec<String>,
+}
+
+/// Result of `fetch_package_info`: the entries that parsed and how many didn't.
+#[derive(Debug, Clone)]
+pub struct PackageInfoReport {
+    pub packages: Vec<PackageInfo>,
+    pub skipped: usize,
+}
+
+/// The subset of `brew info --json=v2` output we read. Entries stay raw JSON
+/// until parsed individually.
+#[derive(Deserialize)]
+struct BrewInfo {
+    #[serde(default)]
+    formulae: Vec<serde_json::Value>,
+    #[serde(default)]
+    casks: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct FormulaInfo {
+    name: String,
+    license: Option<String>,
+    homepage: Option<String>,
+    #[serde(default)]
+    installed: Vec<InstalledVersion>,
+}
+
+#[derive(Deserialize)]
+struct InstalledVersion {
+    /// e.g. "1.7.1", or "HEAD-1a2b3c4" for `--HEAD` installs.
+    #[serde(default)]
+    version: String,
+    /// Unix time the version was installed.
+    time: Option<u64>,
+    /// Non-default build options, e.g. `--with-x`.
+    #[serde(default)]
+    used_options: Vec<String>,
+}
+
+/// The subset of `brew outdated --json=v2` output we read.
+#[derive(Deserialize)]
+struct BrewOutdated {
+    #[serde(default)]
+    formulae: Vec<OutdatedEntry>,
+    #[serde(default)]
+    casks: Vec<OutdatedEntry>,
+}
+
+#[derive(Deserialize)]
+struct OutdatedEntry {
+    name: String,
+}
+
+/// The part of a keg's `INSTALL_RECEIPT.json` we read.
+#[derive(Deserialize)]
+struct InstallReceipt {
+    /// Unix time the keg was installed.
+    time: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct CaskInfo {
+    token: String,
+    homepage: Option<String>,
+    /// Installed version, e.g. "3.0.20".
+    installed: Option<String>,
+    installed_time: Option<u64>,
+    /// Mixed entries like `{"app": ["Firefox.app"]}` or `{"binary": [...]}`.
+    #[serde(default)]
+    artifacts: Vec<serde_json::Value>,
+}
+
+/// Homebrew commands run with their output streamed: repository-wide
+/// housekeeping, plus upgrading a single package.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceAction {
+    /// `brew cleanup`: old versions and stale downloads.
+    Cleanup,
+    /// `brew autoremove`: dependencies nothing depends on anymore.
+    Autoremove,
+    /// `brew cleanup --prune=all`: the entire download cache.
+    PruneCache,
+    /// `brew cleanup --prune-prefix`: broken symlinks and empty directories.
+    PrunePrefix,
+    /// `brew bundle install`: reinstall everything listed in a Brewfile.
+    RestoreBrewfile(PathBuf),
+    /// `brew upgrade`: one package to its latest version.
+    Upgrade(String, PackageType),
+}
+
+impl MaintenanceAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MaintenanceAction::Cleanup => "Cleanup",
+            MaintenanceAction::Autoremove => "Autoremove",
+            MaintenanceAction::PruneCache => "Prune Cache",
+            MaintenanceAction::PrunePrefix => "Prune Broken Links",
+            MaintenanceAction::RestoreBrewfile(_) => "Restore Brewfile",
+            MaintenanceAction::Upgrade(..) => "Upgrade",
+        }
+    }
+
+    /// The command line this action runs, for display.
+    pub fn command(&self) -> String {
+        format!("brew {}", self.args().join(" "))
+    }
+
+    fn args(&self) -> Vec<String> {
+        match self {
+            MaintenanceAction::Cleanup => vec!["cleanup".to_string()],
+            MaintenanceAction::Autoremove => vec!["autoremove".to_string()],
+            MaintenanceAction::PruneCache => {
+                vec!["cleanup".to_string(), "--prune=all".to_string()]
+            }
+            MaintenanceAction::PrunePrefix => {
+                vec!["cleanup".to_string(), "--prune-prefix".to_string()]
+            }
+            MaintenanceAction::RestoreBrewfile(path) => vec![
+                "bundle".to_string(),
+                "install".to_string(),
+                format!("--file={}", path.display()),
+            ],
+            MaintenanceAction::Upgrade(name, package_type) => vec![
+                "upgrade".to_string(),
+                match package_type {
+                    PackageType::Formula => "--formula".to_string(),
+                    PackageType::Cask => "--cask".to_string(),
+                },
+                name.clone(),
+            ],
+        }
+    }
+}
+
+/// What a maintenance action would do, parsed from its `--dry-run` output.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenancePreview {
+    /// Paths or formula names that would be removed.
+    pub items: Vec<String>,
+    /// Brew's own estimate, e.g. "1.2GB", when it prints one.
+    pub freed: Option<String>,
+}
+
+/// Why a scan failed. Each kind maps to its own CLI exit code.
+#[derive(Debug)]
+pub enum ScanError {
+    /// `brew` is missing from PATH or not working.
+    BrewNotFound(String),
+    /// A `brew` command could not be run.
+    CommandFailed(String),
+    /// `brew` (or a fixtures file) produced output we couldn't parse.
+    InvalidOutput(String),
+}
+
+impl ScanError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ScanError::BrewNotFound(_) => 2,
+            ScanError::CommandFailed(_) => 3,
+            ScanError::InvalidOutput(_) => 4,
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::BrewNotFound(message)
+            | ScanError::CommandFailed(message)
+            | ScanError::InvalidOutput(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Wall-clock time spent in each phase of a scan, for `--benchmark`.
+#[derive(Debug, Clone, Default)]
+pub struct ScanTimings {
+    pub prefix: Duration,
+    /// Zero when `brew info` already listed every package.
+    pub list: Duration,
+    pub paths: Duration,
+    pub access_times: Duration,
+    /// Zero for `--fast` scans, which skip sizes.
+    pub sizes: Duration,
+    pub metadata: Duration,
+    pub dependencies: Duration,
+    pub outdated: Duration,
+    pub total: Duration,
 }
+
+impl ScanTimings {
+    /// Each phase with a short label, in the order they run.
+    pub fn phases(&self) -> [(&'static str, Duration); 8] {
+        [
+            ("brew --prefix", self.prefix),
+            ("brew info", self.metadata),
+            ("brew list", self.list),
+            ("path resolution", self.paths),
+            ("access times", self.access_times),
+            ("sizes", self.sizes),
+            ("brew deps", self.dependencies),
+            ("brew outdated", self.outdated),
+        ]
+    }
+}
+
+/// Packages to scan before `estimated_remaining` extrapolates.
+const MIN_SCANNED_FOR_ETA: usize = 3;
+
+/// Scan progress is published every this many packages...
+const PROGRESS_EVERY: usize = 25;
+/// ...or when this long has passed since the last update, whichever is first.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Counts packages finished by parallel scan tasks and decides when progress
+/// is worth writing to the shared state, so the UI's lock isn't taken for
+/// every package.
+struct ProgressThrottle {
+    done: AtomicUsize,
+    last_update: Mutex<Instant>,
+}
+
+impl ProgressThrottle {
+    fn new() -> Self {
+        Self {
+            done: AtomicUsize::new(0),
+            last_update: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Counts one finished package. Returns the count so far when an update
+    /// is due; `None` while another task is already publishing one.
+    fn tick(&self) -> Option<usize> {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut last_update = self.last_update.try_lock().ok()?;
+        if done % PROGRESS_EVERY == 0 || last_update.elapsed() >= PROGRESS_INTERVAL {
+            *last_update = Instant::now();
+            Some(done)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScanningState {
     pub packages_found: usize,
@@ -21,6 +443,11 @@ pub struct ScanningState {
     pub is_paused: bool,
     pub scan_complete: bool,
     pub error_message: Option<String>,
+    /// Non-fatal problem worth reporting once the scan finishes.
+    pub warning: Option<String>,
+    /// Packages the scan could only partly read, with the reason.
+    pub package_warnings: Vec<(String, String)>,
+    pub timings: ScanTimings,
 }
 
 impl ScanningState {
@@ -34,6 +461,9 @@ impl ScanningState {
             is_paused: false,
             scan_complete: false,
             error_message: None,
+            warning: None,
+            package_warnings: Vec::new(),
+            timings: ScanTimings::default(),
         }
     }
 
@@ -50,64 +480,174 @@ impl ScanningState {
     }
 
     pub fn format_elapsed(&self) -> String {
-        let elapsed = self.elapsed_time();
-        let mins = elapsed.as_secs() / 60;
-        let secs = elapsed.as_secs() % 60;
-        format!("{:02}:{:02}", mins, secs)
+        format_clock(self.elapsed_time())
+    }
+
+    /// Time left at the rate so far. `None` until a few packages are scanned,
+    /// as the first ones include brew's startup and are a poor guide.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        if self.packages_scanned < MIN_SCANNED_FOR_ETA || self.total_packages == 0 {
+            return None;
+        }
+        let remaining = self.total_packages.saturating_sub(self.packages_scanned);
+        Some(
+            self.elapsed_time()
+                .mul_f64(remaining as f64 / self.packages_scanned as f64),
+        )
+    }
+
+    pub fn format_remaining(&self) -> String {
+        self.estimated_remaining()
+            .map(format_clock)
+            .unwrap_or_else(|| "--:--".to_string())
+    }
+}
+
+/// `mm:ss`, for the scan's elapsed and remaining time.
+fn format_clock(duration: Duration) -> String {
+    let mins = duration.as_secs() / 60;
+    let secs = duration.as_secs() % 60;
+    format!("{:02}:{:02}", mins, secs)
+}
+
+/// `line` without terminal escape sequences, so brew's colored output reads
+/// as plain text: CSI sequences (colors, cursor movement), OSC sequences
+/// (hyperlinks, titles) and shorter escapes are dropped.
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Parameters and intermediates up to a final byte in '@'..='~'
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Terminated by BEL or ESC '\'
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' {
+                        chars.next_if_eq(&'\\');
+                        break;
+                    }
+                }
+            }
+            // Intermediates, e.g. `ESC ( B` selecting a character set
+            Some(' '..='/') => {
+                while chars.next_if(|c| (' '..='/').contains(c)).is_some() {}
+                chars.next();
+            }
+            _ => {}
+        }
     }
+    plain
 }
 
 impl HomebrewScanner {
+    /// A scanner that runs the real `brew`.
     pub fn new() -> Self {
+        Self::with_backend(BrewCli)
+    }
+
+    /// A scanner that sends its `brew` commands to `backend`.
+    pub fn with_backend(backend: impl BrewBackend + 'static) -> Self {
         Self {
             state: Arc::new(Mutex::new(ScanningState::new())),
             packages: Arc::new(Mutex::new(Vec::new())),
+            broken_links: Arc::new(Mutex::new(Vec::new())),
+            options: ScanOptions::default(),
+            backend: Arc::new(backend),
         }
     }
 
-    fn get_homebrew_prefix() -> Result<PathBuf, String> {
-        let output = Command::new("brew")
-            .args(["--prefix"])
-            .output()
-            .map_err(|e| format!("failed to run 'brew --prefix': {}", e))?;
+    pub fn with_options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether a `brew` executable is on `PATH`, without running it. Always
+    /// true when packages come from a fixture file.
+    pub fn brew_installed() -> bool {
+        if std::env::var_os(FIXTURES_ENV).is_some() {
+            return true;
+        }
+        std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).any(|dir| dir.join("brew").is_file()))
+            .unwrap_or(false)
+    }
+
+    /// The prefix from `HOMEBREW_PREFIX` (set by `brew shellenv`) if it points
+    /// at a directory, otherwise from `brew --prefix`.
+    fn get_homebrew_prefix() -> Result<PathBuf, ScanError> {
+        if let Some(prefix) = std::env::var_os("HOMEBREW_PREFIX").filter(|p| !p.is_empty()) {
+            let prefix = PathBuf::from(prefix);
+            if prefix.is_dir() {
+                return Ok(prefix);
+            }
+        }
+
+        let output = Self::brew_output(&["--prefix"]).map_err(|e| {
+            ScanError::BrewNotFound(format!("failed to run 'brew --prefix': {}", e))
+        })?;
 
         if !output.status.success() {
-            return Err("Hombrew not found or not properly installed.".to_string());
+            return Err(ScanError::BrewNotFound(
+                "Hombrew not found or not properly installed.".to_string(),
+            ));
         }
 
         let prefix = String::from_utf8(output.stdout)
-            .map_err(|e| format!("Invalid UTF-8 in brew --prefix output: {}", e))?
+            .map_err(|e| {
+                ScanError::InvalidOutput(format!("Invalid UTF-8 in brew --prefix output: {}", e))
+            })?
             .trim()
             .to_string();
 
         Ok(PathBuf::from(prefix))
     }
 
-    fn get_installed_packages() -> Result<(Vec<String>, Vec<String>), String> {
-        let formulas_output = Command::new("brew")
-            .args(["list", "--formula"])
-            .output()
-            .map_err(|e| format!("Failed to get foruma list: {}", e))?;
+    fn get_installed_packages(
+        &self,
+        scope: ScanScope,
+    ) -> Result<(Vec<String>, Vec<String>), ScanError> {
+        let formulas = if scope.includes_formulae() {
+            self.backend.list_formulas()?
+        } else {
+            Vec::new()
+        };
 
-        let formulas = if formulas_output.status.success() {
-            String::from_utf8(formulas_output.stdout)
-                .map_err(|e| format!("Invalid UTF-8 in formulas output: {}", e))?
-                .lines()
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
+        let casks = if scope.includes_casks() {
+            self.backend.list_casks()?
         } else {
             Vec::new()
         };
 
-        let casks_output = Command::new("brew")
-            .args(["list", "--cask"])
-            .output()
-            .map_err(|e| format!("Failed to get cask list: {}", e))?;
+        Ok((formulas, casks))
+    }
+
+    fn list_installed(kind_flag: &str) -> Result<Vec<String>, ScanError> {
+        let output = Self::brew_output(&["list", kind_flag]).map_err(|e| {
+            ScanError::CommandFailed(format!("Failed to run 'brew list {}': {}", kind_flag, e))
+        })?;
 
-        let casks = if casks_output.status.success() {
-            String::from_utf8(casks_output.stdout)
-                .map_err(|e| format!("Invalid UTF-8 in casks output: {}", e))?
+        let names = if output.status.success() {
+            String::from_utf8(output.stdout)
+                .map_err(|e| {
+                    ScanError::InvalidOutput(format!(
+                        "Invalid UTF-8 in 'brew list {}' output: {}",
+                        kind_flag, e
+                    ))
+                })?
                 .lines()
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
@@ -116,13 +656,137 @@ impl HomebrewScanner {
             Vec::new()
         };
 
-        Ok((formulas, casks))
+        Ok(names)
+    }
+
+    /// Looks up whether `name` is an installed formula or cask.
+    pub fn find_installed(&self, name: &str) -> Result<Option<PackageType>, ScanError> {
+        if self.backend.list_formulas()?.iter().any(|n| n == name) {
+            return Ok(Some(PackageType::Formula));
+        }
+        if self.backend.list_casks()?.iter().any(|n| n == name) {
+            return Ok(Some(PackageType::Cask));
+        }
+        Ok(None)
+    }
+
+    /// Install paths of one package, for listing what an uninstall would remove.
+    pub fn find_installed_paths(
+        &self,
+        name: &str,
+        package_type: &PackageType,
+    ) -> Result<Vec<String>, ScanError> {
+        let prefix = self.backend.prefix()?;
+        Ok(Self::find_package_paths(&prefix, name, package_type)
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect())
+    }
+
+    /// Runs `brew` to completion, recording it in the command log.
+    fn brew_output(args: &[&str]) -> io::Result<Output> {
+        let start = Instant::now();
+        let output = Command::new("brew")
+            .args(args)
+            .stdin(Stdio::null())
+            .output();
+        command_log::record(args, output.as_ref().map(|o| o.status), start.elapsed());
+        output
+    }
+
+    /// The later of a path's access and modification times. An access time
+    /// older than the last change means atime isn't being kept up to date
+    /// (or isn't supported), so the modification time is the better guess.
+    fn get_file_acess_info(path: &Path) -> Option<(SystemTime, TimeSource)> {
+        let metadata = fs::metadata(path).ok()?;
+        match (metadata.accessed().ok(), metadata.modified().ok()) {
+            (Some(accessed), Some(modified)) if modified > accessed => {
+                Some((modified, TimeSource::Mtime))
+            }
+            (Some(accessed), _) => Some((accessed, TimeSource::Atime)),
+            (None, modified) => modified.map(|modified| (modified, TimeSource::Mtime)),
+        }
+    }
+
+    /// Sums the on-disk size of every file under `paths`, walking directories
+    /// recursively.
+    ///
+    /// Symlinks are counted as links rather than followed, so shared kegs and
+    /// `bin` links don't get double-counted. Blocks inside the walk while the
+    /// scan is paused so a single huge package can be suspended and resumed
+    /// in place.
+    fn calculate_size(&self, paths: &[PathBuf]) -> u64 {
+        let wait = || self.wait_while_paused();
+        paths.iter().map(|path| Self::path_size(path, &wait)).sum()
+    }
+
+    fn wait_while_paused(&self) {
+        loop {
+            {
+                let state = self.state.lock().unwrap();
+                if !state.is_paused || state.scan_complete {
+                    return;
+                }
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    /// On-disk size of each of a package's paths, largest first.
+    pub fn size_breakdown(paths: &[String]) -> Vec<(String, u64)> {
+        let mut sizes: Vec<(String, u64)> = paths
+            .iter()
+            .map(|path| (path.clone(), Self::path_size(Path::new(path), &|| {})))
+            .collect();
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sizes
+    }
+
+    /// Locates Homebrew's download cache with `brew --cache` and measures it.
+    pub fn cache_size() -> Result<(PathBuf, u64), String> {
+        let output = Self::brew_output(&["--cache"])
+            .map_err(|e| format!("Failed to run 'brew --cache': {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "brew --cache failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
+
+        let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        let size = Self::path_size(&dir, &|| {});
+        Ok((dir, size))
+    }
+
+    fn path_size(path: &Path, before_entry: &dyn Fn()) -> u64 {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return 0;
+        };
+
+        if !metadata.is_dir() {
+            return metadata.len();
+        }
+
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| {
+                        before_entry();
+                        Self::path_size(&entry.path(), before_entry)
+                    })
+                    .sum()
+            })
+            .unwrap_or(0)
     }
 
-    fn get_file_acess_info(path: &Path) -> Option<SystemTime> {
-        fs::metadata(path)
-            .ok()
-            .and_then(|metadata| metadata.accessed().ok())
+    /// Where casks install app bundles. Only macOS has one; Homebrew on Linux
+    /// has no casks.
+    fn applications_dir() -> Option<&'static Path> {
+        #[cfg(target_os = "macos")]
+        return Some(Path::new("/Applications"));
+        #[cfg(not(target_os = "macos"))]
+        None
     }
 
     fn find_package_paths(
@@ -156,7 +820,8 @@ impl HomebrewScanner {
                     paths.push(cask_path);
                 }
 
-                if let Ok(entries) = fs::read_dir("/Applications") {
+                let apps = Self::applications_dir().and_then(|dir| fs::read_dir(dir).ok());
+                if let Some(entries) = apps {
                     for entry in entries.flatten() {
                         let app_name = entry.file_name();
                         if let Some(name_str) = app_name.to_str() {
@@ -174,109 +839,773 @@ impl HomebrewScanner {
         paths
     }
 
-    fn scan_packages(&self) -> Result<(), String> {
-        {
-            let mut state = self.state.lock().unwrap();
-            state.current_path = "Getting Hombrew prefix...".to_string();
-        }
+    /// The installed version: the highest keg under `Cellar/<name>` for
+    /// formulae, the version directory under `Caskroom/<name>` for casks.
+    fn installed_version(
+        prefix: &Path,
+        package_name: &str,
+        package_type: &PackageType,
+    ) -> Option<String> {
+        let dir = match package_type {
+            PackageType::Formula => prefix.join("Cellar").join(package_name),
+            PackageType::Cask => prefix.join("Caskroom").join(package_name),
+        };
+        let versions = fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            // Caskroom entries keep their metadata in `.metadata`
+            .filter(|name| !name.starts_with('.'));
+        Self::latest_version(versions)
+    }
+
+    /// When the installed version was poured, from the `time` in its
+    /// `INSTALL_RECEIPT.json`: inside the keg for formulae, under
+    /// `Caskroom/<name>/.metadata` for casks.
+    fn install_receipt_time(
+        prefix: &Path,
+        package_name: &str,
+        package_type: &PackageType,
+        version: Option<&str>,
+    ) -> Option<SystemTime> {
+        let receipt = match package_type {
+            PackageType::Formula => prefix
+                .join("Cellar")
+                .join(package_name)
+                .join(version?)
+                .join("INSTALL_RECEIPT.json"),
+            PackageType::Cask => prefix
+                .join("Caskroom")
+                .join(package_name)
+                .join(".metadata")
+                .join("INSTALL_RECEIPT.json"),
+        };
+        let contents = fs::read(receipt).ok()?;
+        let receipt: InstallReceipt = serde_json::from_slice(&contents).ok()?;
+        Some(UNIX_EPOCH + Duration::from_secs(receipt.time?))
+    }
 
-        let prefix = Self::get_homebrew_prefix()?;
+    /// The highest of `versions`, comparing numeric parts as numbers so that
+    /// "1.10" is newer than "1.9".
+    fn latest_version(versions: impl Iterator<Item = String>) -> Option<String> {
+        versions.max_by(|a, b| Self::compare_versions(a, b))
+    }
 
-        {
-            let mut state = self.state.lock().unwrap();
-            state.current_path = "Getting package list...".to_string();
+    fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+        let parts = |version: &str| -> Vec<String> {
+            version
+                .split(['.', '-', '_', ','])
+                .map(str::to_string)
+                .collect()
+        };
+        let (a, b) = (parts(a), parts(b));
+        for (x, y) in a.iter().zip(&b) {
+            let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            };
+            if ordering.is_ne() {
+                return ordering;
+            }
         }
+        a.len().cmp(&b.len())
+    }
 
-        let (formulas, casks) = Self::get_installed_packages()?;
+    /// The single path whose access time stands in for the whole package in
+    /// fast mode: the `opt/<name>` symlink target (the linked Cellar version)
+    /// for formulae, the Caskroom entry for casks.
+    fn find_representative_path(
+        prefix: &Path,
+        package_name: &str,
+        package_type: &PackageType,
+    ) -> Option<PathBuf> {
+        let path = match package_type {
+            PackageType::Formula => fs::canonicalize(prefix.join("opt").join(package_name))
+                .unwrap_or_else(|_| prefix.join("Cellar").join(package_name)),
+            PackageType::Cask => prefix.join("Caskroom").join(package_name),
+        };
+        path.exists().then_some(path)
+    }
 
-        {
-            let mut state = self.state.lock().unwrap();
-            state.total_packages = formulas.len() + casks.len();
+    fn package_paths(
+        &self,
+        prefix: &Path,
+        package_name: &str,
+        package_type: &PackageType,
+    ) -> Vec<PathBuf> {
+        if self.options.fast {
+            Self::find_representative_path(prefix, package_name, package_type)
+                .into_iter()
+                .collect()
+        } else {
+            Self::find_package_paths(prefix, package_name, package_type)
         }
+    }
 
-        let mut all_packages = Vec::new();
+    /// Install paths named by `brew info` itself, so nothing has to be listed
+    /// on disk: a Cellar keg per installed version (newest first) and the
+    /// `bin` link for formulae, the Caskroom entry and (on macOS) app bundles
+    /// for casks.
+    /// Only paths that exist are kept; a formula without installed versions
+    /// gets none, leaving the caller to look on disk.
+    fn info_paths(prefix: &Path, info: &PackageInfo) -> Vec<PathBuf> {
+        let candidates: Vec<PathBuf> = match info.package_type {
+            PackageType::Formula if info.versions.is_empty() => Vec::new(),
+            PackageType::Formula => info
+                .versions
+                .iter()
+                .map(|version| prefix.join("Cellar").join(&info.name).join(version))
+                .chain(std::iter::once(prefix.join("bin").join(&info.name)))
+                .collect(),
+            PackageType::Cask => std::iter::once(prefix.join("Caskroom").join(&info.name))
+                .chain(
+                    Self::applications_dir()
+                        .into_iter()
+                        .flat_map(|dir| info.apps.iter().map(move |app| dir.join(app))),
+                )
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect()
+    }
 
-        for (i, formula) in formulas.iter().enumerate() {
-            {
-                let state = self.state.lock().unwrap();
-                if state.is_paused && !state.scan_complete {
-                    break;
-                }
+    /// Fetches metadata for every installed package with a single `brew info` call.
+    ///
+    /// Entries are parsed one by one, so a schema change in a few of them only
+    /// drops those packages' metadata; they are counted in `skipped`.
+    pub fn fetch_package_info() -> Result<PackageInfoReport, String> {
+        Self::package_info(&["info", "--json=v2", "--installed"])
+    }
 
-                thread::sleep(Duration::from_millis(100));
-            }
+    /// Fetches metadata for just `package`; `None` if brew's entry for it
+    /// couldn't be parsed.
+    fn fetch_one_package_info(package: &Package) -> Result<Option<PackageInfo>, String> {
+        let type_arg = match package.package_type {
+            PackageType::Formula => "--formula",
+            PackageType::Cask => "--cask",
+        };
+        let report = Self::package_info(&["info", "--json=v2", type_arg, &package.name])?;
+        Ok(report.packages.into_iter().next())
+    }
 
-            {
-                let mut state = self.state.lock().unwrap();
-                state.packages_scanned = i + 1;
-                state.current_path = format!("Scanning formula: {}", formula);
-            }
+    fn package_info(args: &[&str]) -> Result<PackageInfoReport, String> {
+        let output = Self::brew_output(args)
+            .map_err(|e| format!("Failed to run 'brew info --json=v2': {}", e))?;
 
-            let paths = Self::find_package_paths(&prefix, formula, &PackageType::Formula);
-            let (last_accessed, last_accessed_path) = if let Some(path) = paths.first() {
-                (
-                    Self::get_file_acess_info(path),
-                    Some(path.to_string_lossy().to_string()),
-                )
-            } else {
-                (None, None)
-            };
+        if !output.status.success() {
+            return Err(format!(
+                "brew info --json=v2 failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
 
-            let package = Package {
-                name: formula.clone(),
-                package_type: PackageType::Formula,
-                last_accessed,
-                last_accessed_path,
-            };
+        let info: BrewInfo = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid JSON from brew info: {}", e))?;
 
-            all_packages.push(package);
+        let mut skipped = 0;
+        let formulae = Self::parse_entries::<FormulaInfo>(info.formulae, &mut skipped)
+            .into_iter()
+            .map(|mut formula| {
+                // The newest installed version is the one whose files get used
+                formula
+                    .installed
+                    .sort_by_key(|version| std::cmp::Reverse(version.time));
+                let newest = formula.installed.first();
+                let mut used_options = newest.map_or_else(Vec::new, |v| v.used_options.clone());
+                let is_head = newest.is_some_and(|v| v.version.starts_with("HEAD"));
+                if is_head && !used_options.iter().any(|option| option == "--HEAD") {
+                    used_options.push("--HEAD".to_string());
+                }
 
-            {
-                let mut state = self.state.lock().unwrap();
-                state.packages_found = all_packages.len();
+                PackageInfo {
+                    name: formula.name,
+                    package_type: PackageType::Formula,
+                    metadata: PackageMetadata {
+                        license: formula.license,
+                        homepage: formula.homepage,
+                        used_options,
+                    },
+                    installed_at: newest
+                        .and_then(|version| version.time)
+                        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                    versions: formula
+                        .installed
+                        .iter()
+                        .map(|version| version.version.clone())
+                        .collect(),
+                    apps: Vec::new(),
+                }
+            });
+        let casks = Self::parse_entries::<CaskInfo>(info.casks, &mut skipped)
+            .into_iter()
+            .map(|cask| PackageInfo {
+                name: cask.token,
+                package_type: PackageType::Cask,
+                metadata: PackageMetadata {
+                    homepage: cask.homepage,
+                    ..PackageMetadata::default()
+                },
+                installed_at: cask
+                    .installed_time
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                versions: cask.installed.into_iter().collect(),
+                apps: cask
+                    .artifacts
+                    .iter()
+                    .filter_map(|artifact| artifact.get("app")?.as_array())
+                    .flatten()
+                    .filter_map(|app| app.as_str().map(str::to_string))
+                    .collect(),
+            });
+
+        Ok(PackageInfoReport {
+            packages: formulae.chain(casks).collect(),
+            skipped,
+        })
+    }
+
+    fn parse_entries<T: DeserializeOwned>(
+        entries: Vec<serde_json::Value>,
+        skipped: &mut usize,
+    ) -> Vec<T> {
+        entries
+            .into_iter()
+            .filter_map(|entry| {
+                let parsed = serde_json::from_value(entry).ok();
+                *skipped += usize::from(parsed.is_none());
+                parsed
+            })
+            .collect()
+    }
+
+    /// Symlinks in `<prefix>/bin` whose target no longer exists, usually left
+    /// behind by a removed Cellar version.
+    pub fn find_broken_links(prefix: &Path) -> Vec<BrokenLink> {
+        let Ok(entries) = fs::read_dir(prefix.join("bin")) else {
+            return Vec::new();
+        };
+
+        let mut broken: Vec<BrokenLink> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| !path.exists())
+            .filter_map(|path| {
+                // Fails for anything that isn't a symlink
+                let target = fs::read_link(&path).ok()?;
+                Some(BrokenLink { path, target })
+            })
+            .collect();
+        broken.sort_by(|a, b| a.path.cmp(&b.path));
+        broken
+    }
+
+    /// Maps each installed formula to its direct dependencies, from one
+    /// `brew deps --installed` call.
+    pub fn fetch_dependencies() -> Result<HashMap<String, Vec<String>>, String> {
+        let output = Self::brew_output(&["deps", "--installed"])
+            .map_err(|e| format!("Failed to run 'brew deps --installed': {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "brew deps --installed failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, deps)| {
+                let deps = deps.split_whitespace().map(str::to_string).collect();
+                (name.trim().to_string(), deps)
+            })
+            .collect())
+    }
+
+    /// Installed formulae and casks with a newer version available, from
+    /// `brew outdated --json=v2`.
+    pub fn fetch_outdated() -> Result<Vec<(String, PackageType)>, String> {
+        let output = Self::brew_output(&["outdated", "--json=v2"])
+            .map_err(|e| format!("Failed to run 'brew outdated': {}", e))?;
+
+        // `brew outdated` exits non-zero when anything is outdated, so only
+        // the JSON tells success from failure
+        let outdated: BrewOutdated = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid JSON from brew outdated: {}", e))?;
+
+        let formulae = outdated
+            .formulae
+            .into_iter()
+            .map(|entry| (entry.name, PackageType::Formula));
+        let casks = outdated
+            .casks
+            .into_iter()
+            .map(|entry| (entry.name, PackageType::Cask));
+        Ok(formulae.chain(casks).collect())
+    }
+
+    /// Installed formulae that depend on `name` directly, from
+    /// `brew uses --installed`, sorted by name.
+    pub fn fetch_uses(name: &str) -> Result<Vec<String>, String> {
+        let output = Self::brew_output(&["uses", "--installed", name])
+            .map_err(|e| format!("Failed to run 'brew uses --installed': {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "brew uses --installed failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
+
+        let mut uses: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        uses.sort();
+        Ok(uses)
+    }
+
+    /// Packages `package` depends on directly, from `brew deps --direct`,
+    /// sorted by name.
+    pub fn fetch_deps(package: &Package) -> Result<Vec<String>, String> {
+        let type_arg = match package.package_type {
+            PackageType::Formula => "--formula",
+            PackageType::Cask => "--cask",
+        };
+        let output = Self::brew_output(&["deps", "--direct", type_arg, &package.name])
+            .map_err(|e| format!("Failed to run 'brew deps --direct': {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "brew deps --direct failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
+
+        let mut deps: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        deps.sort();
+        Ok(deps)
+    }
+
+    /// Inverts the dependency map: for each package, the installed packages that
+    /// depend on it directly, sorted by name.
+    pub fn dependents(dependencies: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, deps) in dependencies {
+            for dep in deps {
+                dependents
+                    .entry(dep.clone())
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+        for names in dependents.values_mut() {
+            names.sort();
+        }
+        dependents
+    }
+
+    /// For every package, how many others would be left with no dependents if
+    /// it were removed, following the chain through dependencies of those too.
+    pub fn orphan_counts(dependencies: &HashMap<String, Vec<String>>) -> HashMap<String, usize> {
+        let mut dependents: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for (name, deps) in dependencies {
+            for dep in deps {
+                dependents.entry(dep).or_default().insert(name);
             }
         }
 
-        for (i, cask) in casks.iter().enumerate() {
+        dependencies
+            .keys()
+            .map(|name| {
+                let mut removed: HashSet<&str> = HashSet::from([name.as_str()]);
+                let mut pending: Vec<&str> = vec![name];
+
+                while let Some(current) = pending.pop() {
+                    for dep in dependencies.get(current).into_iter().flatten() {
+                        let orphaned = !removed.contains(dep.as_str())
+                            && dependents
+                                .get(dep.as_str())
+                                .is_some_and(|users| users.iter().all(|u| removed.contains(u)));
+                        if orphaned {
+                            removed.insert(dep);
+                            pending.push(dep);
+                        }
+                    }
+                }
+
+                (name.clone(), removed.len() - 1)
+            })
+            .collect()
+    }
+
+    /// Packages the scan couldn't fully resolve. Missing metadata only counts
+    /// when `brew info` itself worked, otherwise every package would be listed.
+    fn package_warnings(packages: &[Package], metadata_fetched: bool) -> Vec<(String, String)> {
+        packages
+            .iter()
+            .filter_map(|package| {
+                let reason = if package.paths.is_empty() {
+                    "No install path found"
+                } else if metadata_fetched && package.metadata.is_none() {
+                    "No metadata from brew info"
+                } else {
+                    return None;
+                };
+                Some((package.name.clone(), reason.to_string()))
+            })
+            .collect()
+    }
+
+    fn apply_package_info(packages: &mut [Package], infos: &[PackageInfo]) {
+        for package in packages.iter_mut() {
+            if let Some(info) = infos
+                .iter()
+                .find(|info| info.name == package.name && info.package_type == package.package_type)
             {
-                let state = self.state.lock().unwrap();
-                if state.is_paused && !state.scan_complete {
-                    break;
+                package.metadata = Some(info.metadata.clone());
+                package.installed_at = package.installed_at.or(info.installed_at);
+            }
+        }
+    }
+
+    /// Resolves the configured per-cask data locations for `cask_name`.
+    ///
+    /// Entries containing `{name}` are expanded to a single path; plain
+    /// directories are searched for entries whose name contains the cask name.
+    fn find_cask_data_paths(dirs: &[String], cask_name: &str) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        let needle = cask_name.to_lowercase();
+
+        for dir in dirs {
+            let dir = Self::expand_home(dir);
+            if dir.contains("{name}") {
+                let path = PathBuf::from(dir.replace("{name}", cask_name));
+                if path.exists() {
+                    paths.push(path);
+                }
+            } else if let Ok(entries) = fs::read_dir(&dir) {
+                for entry in entries.flatten() {
+                    if entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| name.to_lowercase().contains(&needle))
+                    {
+                        paths.push(entry.path());
+                    }
                 }
-                thread::sleep(Duration::from_millis(100));
             }
+        }
+        paths
+    }
 
-            {
-                let mut state = self.state.lock().unwrap();
-                state.packages_scanned = formulas.len() + i + 1;
-                state.current_path = format!("Scanning cask: {}", cask);
+    pub fn expand_home(path: &str) -> String {
+        match (path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+            _ => path.to_string(),
+        }
+    }
+
+    /// When a package was last used: the first path's access time, or for casks
+    /// with extra data dirs configured, the most recent of all of them.
+    fn access_info(
+        &self,
+        name: &str,
+        package_type: &PackageType,
+        paths: &[PathBuf],
+    ) -> (Option<(SystemTime, TimeSource)>, Option<String>) {
+        #[cfg(target_os = "macos")]
+        if matches!(package_type, PackageType::Cask) {
+            if let Some((time, app)) = Self::app_last_used(paths) {
+                return (
+                    Some((time, TimeSource::Atime)),
+                    Some(app.to_string_lossy().to_string()),
+                );
             }
+        }
 
-            let paths = Self::find_package_paths(&prefix, cask, &PackageType::Cask);
-            let (last_accessed, last_accessed_path) = if let Some(path) = paths.first() {
-                (
-                    Self::get_file_acess_info(path),
-                    Some(path.to_string_lossy().to_string()),
-                )
-            } else {
-                (None, None)
-            };
+        if matches!(package_type, PackageType::Cask) && !self.options.cask_data_dirs.is_empty() {
+            let mut candidates = paths.to_vec();
+            candidates.extend(Self::find_cask_data_paths(
+                &self.options.cask_data_dirs,
+                name,
+            ));
+            return Self::most_recent_access(&candidates);
+        }
 
-            let package = Package {
-                name: cask.clone(),
-                package_type: PackageType::Cask,
-                last_accessed,
-                last_accessed_path,
-            };
+        match paths.first() {
+            Some(path) => (
+                Self::get_file_acess_info(path),
+                Some(path.to_string_lossy().to_string()),
+            ),
+            None => (None, None),
+        }
+    }
 
-            all_packages.push(package);
+    /// When a cask's app bundle was last opened, from Spotlight's
+    /// `kMDItemLastUsedDate`. macOS rarely updates atime on app bundles, so this
+    /// is a much better signal. `None` when no path is an app under
+    /// `/Applications` or Spotlight has no date for it.
+    #[cfg(target_os = "macos")]
+    fn app_last_used(paths: &[PathBuf]) -> Option<(SystemTime, &PathBuf)> {
+        let applications = Self::applications_dir()?;
+        paths
+            .iter()
+            .filter(|path| path.starts_with(applications))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "app"))
+            .filter_map(|app| Some((Self::spotlight_last_used(app)?, app)))
+            .max_by_key(|(time, _)| *time)
+    }
 
-            {
-                let mut state = self.state.lock().unwrap();
-                state.packages_found = all_packages.len();
+    #[cfg(target_os = "macos")]
+    fn spotlight_last_used(app: &Path) -> Option<SystemTime> {
+        let output = Command::new("mdls")
+            .args(["-name", "kMDItemLastUsedDate", "-raw"])
+            .arg(app)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // e.g. "2024-03-12 09:41:07 +0000", or "(null)" when never recorded
+        let raw = String::from_utf8_lossy(&output.stdout);
+        let date = chrono::DateTime::parse_from_str(raw.trim(), "%Y-%m-%d %H:%M:%S %z").ok()?;
+        Some(date.into())
+    }
+
+    /// Picks the most recently accessed of `paths`.
+    fn most_recent_access(paths: &[PathBuf]) -> (Option<(SystemTime, TimeSource)>, Option<String>) {
+        paths
+            .iter()
+            .filter_map(|path| Some((Self::get_file_acess_info(path)?, path)))
+            .max_by_key(|((time, _), _)| *time)
+            .map(|(access, path)| (Some(access), Some(path.to_string_lossy().to_string())))
+            .unwrap_or_else(|| {
+                let first = paths.first().map(|p| p.to_string_lossy().to_string());
+                (None, first)
+            })
+    }
+
+    fn scan_packages(&self) -> Result<(), ScanError> {
+        let scan_start = Instant::now();
+        let mut timings = ScanTimings::default();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.current_path = "Getting Hombrew prefix...".to_string();
+        }
+
+        let phase = Instant::now();
+        let prefix = self.backend.prefix()?;
+        timings.prefix = phase.elapsed();
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.current_path = "Fetching package info...".to_string();
+        }
+
+        // One `brew info` call lists the packages, names their install paths
+        // and carries their metadata. If it fails or any entry doesn't parse,
+        // `brew list` provides the names instead, so no package goes missing,
+        // and paths are found on disk.
+        let phase = Instant::now();
+        let info = self.backend.info();
+        timings.metadata = phase.elapsed();
+        let complete_info = info.as_ref().ok().filter(|report| report.skipped == 0);
+
+        let phase = Instant::now();
+        let (formulas, casks) = match complete_info {
+            Some(_) => (Vec::new(), Vec::new()),
+            None => {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.current_path = "Getting package list...".to_string();
+                }
+                self.get_installed_packages(self.options.scope)?
             }
+        };
+        timings.list = phase.elapsed();
+
+        let scope = self.options.scope;
+        let to_scan: Vec<(&String, PackageType, Option<&PackageInfo>)> = match complete_info {
+            Some(report) => report
+                .packages
+                .iter()
+                .filter(|info| match info.package_type {
+                    PackageType::Formula => scope.includes_formulae(),
+                    PackageType::Cask => scope.includes_casks(),
+                })
+                .map(|info| (&info.name, info.package_type.clone(), Some(info)))
+                .collect(),
+            None => formulas
+                .iter()
+                .map(|name| (name, PackageType::Formula, None))
+                .chain(casks.iter().map(|name| (name, PackageType::Cask, None)))
+                .collect(),
+        };
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.total_packages = to_scan.len();
         }
 
+        // Locating paths and reading access times is independent stat work per
+        // package, so it runs across rayon's pool; sizes are walked afterwards,
+        // also in parallel.
+        let phase = Instant::now();
+        let located: Vec<(&String, PackageType, Vec<PathBuf>, Option<String>)> = to_scan
+            .into_par_iter()
+            .map(|(name, package_type, info)| {
+                self.wait_while_paused();
+                let version = match info {
+                    Some(info) if !info.versions.is_empty() => {
+                        Self::latest_version(info.versions.iter().cloned())
+                    }
+                    _ => Self::installed_version(&prefix, name, &package_type),
+                };
+                let paths = match info.filter(|_| !self.options.fast) {
+                    Some(info) => {
+                        let paths = Self::info_paths(&prefix, info);
+                        if paths.is_empty() {
+                            self.package_paths(&prefix, name, &package_type)
+                        } else {
+                            paths
+                        }
+                    }
+                    None => self.package_paths(&prefix, name, &package_type),
+                };
+                (name, package_type, paths, version)
+            })
+            .collect();
+        timings.paths = phase.elapsed();
+
+        let phase = Instant::now();
+        let progress = ProgressThrottle::new();
+        let mut all_packages: Vec<Package> = located
+            .par_iter()
+            .map(|(name, package_type, paths, version)| {
+                self.wait_while_paused();
+
+                let (last_accessed, last_accessed_path) =
+                    self.access_info(name, package_type, paths);
+
+                if let Some(scanned) = progress.tick() {
+                    // Tasks finish in any order, so never move the count backwards
+                    let mut state = self.state.lock().unwrap();
+                    state.packages_scanned = state.packages_scanned.max(scanned);
+                    state.current_path = match package_type {
+                        PackageType::Formula => format!("Scanning formula: {}", name),
+                        PackageType::Cask => format!("Scanning cask: {}", name),
+                    };
+                }
+
+                let package = Package {
+                    name: (*name).clone(),
+                    package_type: package_type.clone(),
+                    version: version.clone(),
+                    last_accessed: last_accessed.map(|(time, _)| time),
+                    time_source: last_accessed.map(|(_, source)| source).unwrap_or_default(),
+                    last_accessed_path,
+                    installed_at: Self::install_receipt_time(
+                        &prefix,
+                        name,
+                        package_type,
+                        version.as_deref(),
+                    ),
+                    size_bytes: None,
+                    metadata: None,
+                    orphans: None,
+                    dependents: Vec::new(),
+                    outdated: false,
+                    paths: paths
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect(),
+                };
+                // Sized packages are logged once measured below
+                if self.options.fast {
+                    scan_log::record(&package);
+                }
+                package
+            })
+            .collect();
+        timings.access_times = phase.elapsed();
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.packages_scanned = all_packages.len();
+            state.packages_found = all_packages.len();
+        }
+
+        let phase = Instant::now();
+        let progress = ProgressThrottle::new();
+        if !self.options.fast {
+            // Walks fill in sizes in place, so the package order stays as listed
+            all_packages.par_iter_mut().for_each(|package| {
+                // Pausing suspends the scan here; it picks up with this package on resume
+                self.wait_while_paused();
+
+                if progress.tick().is_some() {
+                    let mut state = self.state.lock().unwrap();
+                    state.current_path = format!("Measuring size: {}", package.name);
+                }
+
+                let paths: Vec<PathBuf> = package.paths.iter().map(PathBuf::from).collect();
+                package.size_bytes = Some(self.calculate_size(&paths));
+                scan_log::record(package);
+            });
+        }
+        timings.sizes = phase.elapsed();
+
+        // Metadata is a nice-to-have; a failing `brew info` shouldn't fail the scan
+        let (warning, metadata_fetched) = match &info {
+            Ok(report) => {
+                Self::apply_package_info(&mut all_packages, &report.packages);
+                let warning = (report.skipped > 0).then(|| {
+                    format!(
+                        "Could not parse brew metadata for {} package(s)",
+                        report.skipped
+                    )
+                });
+                (warning, true)
+            }
+            Err(e) => (Some(format!("Package metadata unavailable: {}", e)), false),
+        };
+        {
+            let mut state = self.state.lock().unwrap();
+            state.warning = warning;
+            state.package_warnings = Self::package_warnings(&all_packages, metadata_fetched);
+        }
+
+        *self.broken_links.lock().unwrap() = Self::find_broken_links(&prefix);
+
+        let phase = Instant::now();
+        if let Ok(dependencies) = self.backend.dependencies() {
+            let orphans = Self::orphan_counts(&dependencies);
+            let mut dependents = Self::dependents(&dependencies);
+            for package in all_packages.iter_mut() {
+                package.orphans = orphans.get(&package.name).copied();
+                package.dependents = dependents.remove(&package.name).unwrap_or_default();
+            }
+        }
+        timings.dependencies = phase.elapsed();
+
+        let phase = Instant::now();
+        if let Ok(outdated) = self.backend.outdated() {
+            for package in all_packages.iter_mut() {
+                package.outdated = outdated
+                    .iter()
+                    .any(|(name, kind)| *name == package.name && *kind == package.package_type);
+            }
+        }
+        timings.outdated = phase.elapsed();
+        timings.total = scan_start.elapsed();
+
         {
             let mut packages = self.packages.lock().unwrap();
             packages.clear();
@@ -285,31 +1614,136 @@ impl HomebrewScanner {
 
         {
             let mut state = self.state.lock().unwrap();
+            state.timings = timings;
             state.scan_complete = true;
             state.current_path = "Scan complete!".to_string();
         }
         Ok(())
     }
 
+    /// Populates `packages` from a fixture file (see [`FIXTURES_ENV`]).
+    fn load_fixtures(&self, path: &Path) -> Result<(), ScanError> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            ScanError::InvalidOutput(format!("Failed to read fixtures {}: {}", path.display(), e))
+        })?;
+        let fixtures: Vec<Package> = serde_json::from_str(&contents).map_err(|e| {
+            ScanError::InvalidOutput(format!("Invalid fixtures {}: {}", path.display(), e))
+        })?;
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.total_packages = fixtures.len();
+            state.packages_scanned = fixtures.len();
+            state.packages_found = fixtures.len();
+        }
+
+        self.state.lock().unwrap().package_warnings = Self::package_warnings(&fixtures, true);
+        fixtures.iter().for_each(scan_log::record);
+        {
+            let mut packages = self.packages.lock().unwrap();
+            packages.clear();
+            packages.extend(fixtures);
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.scan_complete = true;
+            state.current_path = format!("Loaded fixtures from {}", path.display());
+        }
+        Ok(())
+    }
+
     pub fn start_scan(&self) -> thread::JoinHandle<()> {
         let scanner = HomebrewScanner {
             state: Arc::clone(&self.state),
             packages: Arc::clone(&self.packages),
+            broken_links: Arc::clone(&self.broken_links),
+            options: self.options.clone(),
+            backend: Arc::clone(&self.backend),
         };
 
         thread::spawn(move || {
-            if let Err(e) = scanner.scan_packages() {
+            if let Err(e) = scanner.run_scan() {
                 let mut state = scanner.state.lock().unwrap();
-                state.error_message = Some(e);
+                state.error_message = Some(e.to_string());
                 state.scan_complete = true;
             }
         })
     }
 
+    fn run_scan(&self) -> Result<(), ScanError> {
+        match std::env::var_os(FIXTURES_ENV) {
+            Some(path) => self.load_fixtures(Path::new(&path)),
+            None => self.scan_packages(),
+        }
+    }
+
+    /// Re-reads access times of already scanned packages, skipping the path
+    /// lookup and size walk. Fixture packages are left as loaded.
+    pub fn refresh_access_times(&self, packages: &mut [Package]) {
+        if std::env::var_os(FIXTURES_ENV).is_some() {
+            return;
+        }
+
+        packages.par_iter_mut().for_each(|package| {
+            let paths: Vec<PathBuf> = package.paths.iter().map(PathBuf::from).collect();
+            let (last_accessed, last_accessed_path) =
+                self.access_info(&package.name, &package.package_type, &paths);
+            package.set_last_accessed(last_accessed);
+            package.last_accessed_path = last_accessed_path;
+        });
+    }
+
+    /// Re-resolves one package's paths, access time, version, metadata and,
+    /// unless scanning fast, size. Orphans and dependents are left as they were.
+    pub fn refresh_package(&self, package: &mut Package) -> Result<(), ScanError> {
+        if std::env::var_os(FIXTURES_ENV).is_some() {
+            return Ok(());
+        }
+
+        let prefix = self.backend.prefix()?;
+        let paths = self.package_paths(&prefix, &package.name, &package.package_type);
+        package.version = Self::installed_version(&prefix, &package.name, &package.package_type);
+        // Metadata is a nice-to-have here too; the rest of the refresh stands without it
+        if let Ok(Some(info)) = self.backend.info_for(package) {
+            package.metadata = Some(info.metadata);
+            package.installed_at = info.installed_at;
+        }
+        package.installed_at = Self::install_receipt_time(
+            &prefix,
+            &package.name,
+            &package.package_type,
+            package.version.as_deref(),
+        )
+        .or(package.installed_at);
+        let (last_accessed, last_accessed_path) =
+            self.access_info(&package.name, &package.package_type, &paths);
+        package.set_last_accessed(last_accessed);
+        package.last_accessed_path = last_accessed_path;
+        if !self.options.fast {
+            package.size_bytes = Some(self.calculate_size(&paths));
+        }
+        package.paths = paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        Ok(())
+    }
+
+    /// Scans on the current thread and returns the packages, for non-interactive use.
+    pub fn scan_blocking(&self) -> Result<Vec<Package>, ScanError> {
+        self.run_scan()?;
+        Ok(self.get_packages())
+    }
+
     pub fn get_state(&self) -> ScanningState {
         self.state.lock().unwrap().clone()
     }
 
+    pub fn get_broken_links(&self) -> Vec<BrokenLink> {
+        self.broken_links.lock().unwrap().clone()
+    }
+
     pub fn get_packages(&self) -> Vec<Package> {
         self.packages.lock().unwrap().clone()
     }
@@ -319,8 +1753,317 @@ impl HomebrewScanner {
         state.is_paused = !state.is_paused;
     }
 
+    /// Installed size as reported by `brew info`, summed across installed versions.
+    ///
+    /// Parses the human-readable keg lines, e.g.
+    /// `/opt/homebrew/Cellar/wget/1.24.5 (92 files, 4.5MB) *`.
+    pub fn fetch_reported_size(package: &Package) -> Option<u64> {
+        let kind_flag = match package.package_type {
+            PackageType::Formula => "--formula",
+            PackageType::Cask => "--cask",
+        };
+        let output = Self::brew_output(&["info", kind_flag, &package.name]).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let sizes: Vec<u64> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.starts_with('/'))
+            .filter_map(|line| {
+                let details = line.split_once('(')?.1.split_once(')')?.0;
+                Self::parse_brew_size(details.rsplit(", ").next()?)
+            })
+            .collect();
+
+        (!sizes.is_empty()).then(|| sizes.iter().sum())
+    }
+
+    /// Parses brew's `disk_usage_readable` format (`812B`, `4.5MB`, `1.2GB`).
+    fn parse_brew_size(text: &str) -> Option<u64> {
+        let text = text.trim();
+        let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+        let (number, unit) = text.split_at(split);
+        let multiplier = match unit {
+            "B" => 1.0,
+            "KB" => 1024.0,
+            "MB" => 1024.0 * 1024.0,
+            "GB" => 1024.0 * 1024.0 * 1024.0,
+            _ => return None,
+        };
+        let value: f64 = number.trim().parse().ok()?;
+        Some((value * multiplier) as u64)
+    }
+
+    /// Returns the recorded paths of `package` that no longer exist on disk.
+    ///
+    /// Dangling symlinks (e.g. a `bin` link into a removed keg) count as missing.
+    pub fn find_missing_paths(package: &Package) -> Vec<String> {
+        package
+            .paths
+            .iter()
+            .filter(|path| !Path::new(path).exists())
+            .cloned()
+            .collect()
+    }
+
+    /// Shows `path` in the system file manager: selected in Finder on macOS,
+    /// or its directory opened with `xdg-open` elsewhere.
+    pub fn reveal_in_file_manager(path: &str) -> Result<(), String> {
+        let path = Path::new(path);
+        if !path.exists() {
+            return Err(format!("{} no longer exists", path.display()));
+        }
+
+        let command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("open");
+            command.arg("-R").arg(path);
+            command
+        } else {
+            let dir = if path.is_dir() {
+                path
+            } else {
+                path.parent().unwrap_or(path)
+            };
+            let mut command = Command::new("xdg-open");
+            command.arg(dir);
+            command
+        };
+        Self::spawn_detached(command).map_err(|e| format!("Failed to open file manager: {}", e))
+    }
+
+    /// Opens `package`'s homepage in the browser: the URL `brew info` gave
+    /// during the scan, or `brew home` when there is none on record.
+    pub fn open_homepage(package: &Package) -> Result<(), String> {
+        let command = match package.homepage() {
+            Some(url) => {
+                let opener = if cfg!(target_os = "macos") {
+                    "open"
+                } else {
+                    "xdg-open"
+                };
+                let mut command = Command::new(opener);
+                command.arg(url);
+                command
+            }
+            None => {
+                let type_arg = match package.package_type {
+                    PackageType::Formula => "--formula",
+                    PackageType::Cask => "--cask",
+                };
+                let mut command = Command::new("brew");
+                command.args(["home", type_arg, &package.name]);
+                command
+            }
+        };
+        Self::spawn_detached(command).map_err(|e| format!("Failed to open homepage: {}", e))
+    }
+
+    /// Starts `command` with no stdio and doesn't wait for it. `brew`
+    /// commands are logged once they exit.
+    fn spawn_detached(mut command: Command) -> io::Result<()> {
+        let brew_args: Option<Vec<String>> = (command.get_program() == "brew").then(|| {
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        });
+        let log = move |outcome: Result<ExitStatus, &io::Error>, elapsed| {
+            if let Some(ref args) = brew_args {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                command_log::record(&args, outcome, elapsed);
+            }
+        };
+
+        let start = Instant::now();
+        let mut child = match command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                log(Err(&e), start.elapsed());
+                return Err(e);
+            }
+        };
+        // Some openers stay around until the window closes; reap them off-thread
+        thread::spawn(move || {
+            let status = child.wait();
+            log(status.as_ref().copied(), start.elapsed());
+        });
+        Ok(())
+    }
+
+    /// Runs `action` with `--dry-run` and collects what it would remove.
+    pub fn preview_maintenance(action: &MaintenanceAction) -> Result<MaintenancePreview, String> {
+        if let MaintenanceAction::RestoreBrewfile(path) = action {
+            return Self::preview_bundle_install(path);
+        }
+
+        let mut args = action.args();
+        args.push("--dry-run".to_string());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = Self::brew_output(&args)
+            .map_err(|e| format!("Failed to run brew {} --dry-run: {}", args[0], e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "brew {} --dry-run failed with exit code: {:?}",
+                args[0],
+                output.status.code()
+            ));
+        }
+
+        Ok(Self::parse_dry_run(
+            action,
+            &String::from_utf8_lossy(&output.stdout),
+        ))
+    }
+
+    /// `brew bundle` has no dry run; `brew bundle check --verbose` lists what
+    /// an install would add instead, exiting non-zero when anything is missing.
+    fn preview_bundle_install(brewfile: &Path) -> Result<MaintenancePreview, String> {
+        if !brewfile.is_file() {
+            return Err(format!("No Brewfile at {}", brewfile.display()));
+        }
+
+        let file_arg = format!("--file={}", brewfile.display());
+        let output = Self::brew_output(&["bundle", "check", "--verbose", &file_arg])
+            .map_err(|e| format!("Failed to run brew bundle check: {}", e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let items: Vec<String> = stdout
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim().trim_start_matches('→').trim();
+                line.strip_suffix(" needs to be installed or updated.")
+                    .map(|entry| entry.to_string())
+            })
+            .collect();
+
+        if items.is_empty() && !output.status.success() {
+            return Err(format!(
+                "brew bundle check failed with exit code: {:?}",
+                output.status.code()
+            ));
+        }
+
+        Ok(MaintenancePreview { items, freed: None })
+    }
+
+    fn parse_dry_run(action: &MaintenanceAction, stdout: &str) -> MaintenancePreview {
+        let mut preview = MaintenancePreview::default();
+
+        for line in stdout.lines().map(str::trim) {
+            if let Some((_, rest)) = line.split_once("would free approximately ") {
+                preview.freed = rest.split_whitespace().next().map(|size| size.to_string());
+            } else if let Some(path) = line.strip_prefix("Would remove: ") {
+                preview.items.push(path.to_string());
+            } else if *action == MaintenanceAction::Autoremove
+                && !line.is_empty()
+                && !line.starts_with("==>")
+            {
+                // Autoremove lists bare formula names under a "Would autoremove" header
+                preview.items.push(line.to_string());
+            }
+        }
+
+        preview
+    }
+
+    /// Runs `action` for real, streaming its output like an uninstall.
+    pub fn run_maintenance_with_output(
+        action: MaintenanceAction,
+        cancel: &CancelHandle,
+        output_sender: mpsc::Sender<String>,
+    ) -> Result<(), String> {
+        let _ = output_sender.send(format!("$ {}", action.command()));
+        let _ = output_sender.send("".to_string());
+
+        let args = action.args();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let start = Instant::now();
+        let mut child = Command::new("brew")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                command_log::record(&args, Err(&e), start.elapsed());
+                format!("Failed to start brew {}: {}", args[0], e)
+            })?;
+        let stdout = child.stdout.take();
+        let stderr = Self::collect_stderr(child.stderr.take());
+        cancel.attach(child);
+
+        if let Some(stdout) = stdout {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line_content) => {
+                        let _ = output_sender.send(strip_ansi(&line_content));
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+
+        let mut child = cancel
+            .detach()
+            .ok_or_else(|| format!("brew {} was detached while running", args[0]))?;
+        let exit_status = child.wait();
+        command_log::record(&args, exit_status.as_ref().copied(), start.elapsed());
+        let exit_status =
+            exit_status.map_err(|e| format!("Failed to wait for brew process: {}", e))?;
+
+        let stderr = stderr.join().unwrap_or_default();
+        if !exit_status.success() {
+            for line in stderr {
+                let _ = output_sender.send(strip_ansi(&line));
+            }
+            return Err(format!(
+                "brew {} failed with exit code: {:?}",
+                args[0],
+                exit_status.code()
+            ));
+        }
+
+        let _ = output_sender.send("".to_string());
+        let _ = output_sender.send(format!("✅ {} completed successfully!", action.label()));
+
+        Ok(())
+    }
+
+    /// Reads `stderr` to the end on its own thread, so a command that fills
+    /// the pipe can't block while stdout is still being streamed.
+    fn collect_stderr(stderr: Option<ChildStderr>) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            stderr
+                .map(|stderr| {
+                    BufReader::new(stderr)
+                        .lines()
+                        .map_while(Result::ok)
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Whether a line of brew's stderr shows sudo asking for a password we can't supply.
+    fn is_sudo_password_prompt(line: &str) -> bool {
+        let line = line.to_lowercase();
+        line.contains("sudo")
+            && (line.contains("password")
+                || line.contains("terminal is required")
+                || line.contains("askpass"))
+    }
+
     pub fn delete_package_with_output(
+        &self,
         package: &Package,
+        options: &UninstallOptions,
         output_sender: mpsc::Sender<String>,
     ) -> Result<(), String> {
         let package_arg = match package.package_type {
@@ -328,26 +2071,50 @@ impl HomebrewScanner {
             PackageType::Cask => "--cask",
         };
 
+        let mut args = vec!["uninstall"];
+        if options.force {
+            args.push("--force");
+        }
+        if options.zap && package.package_type == PackageType::Cask {
+            args.push("--zap");
+        }
+        args.extend([package_arg, package.name.as_str()]);
+
         // Send initial command info
-        let command_line = format!("$ brew uninstall {} {}", package_arg, package.name);
+        let command_line = format!("$ brew {}", args.join(" "));
         let _ = output_sender.send(command_line);
         let _ = output_sender.send("".to_string()); // Empty line
 
+        if options.dry_run {
+            let _ = output_sender.send("Dry run: nothing will be removed".to_string());
+            for path in &package.paths {
+                let _ = output_sender.send(format!("Would remove: {}", path));
+            }
+            if options.zap && package.package_type == PackageType::Cask {
+                let _ = output_sender.send(
+                    "Would remove: the app's preferences and support files (--zap)".to_string(),
+                );
+            }
+            return Ok(());
+        }
+
         // Start the brew uninstall process with piped output
-        let mut child = Command::new("brew")
-            .args(["uninstall", package_arg, &package.name])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to start brew uninstall: {}", e))?;
+        let start = Instant::now();
+        let mut child = self.backend.uninstall(package, &args).map_err(|e| {
+            command_log::record(&args, Err(&e), start.elapsed());
+            format!("Failed to start brew uninstall: {}", e)
+        })?;
+        let stdout = child.stdout.take();
+        let stderr = Self::collect_stderr(child.stderr.take());
+        options.cancel.attach(child);
 
         // Read stdout in real-time
-        if let Some(stdout) = child.stdout.take() {
+        if let Some(stdout) = stdout {
             let reader = BufReader::new(stdout);
             for line in reader.lines() {
                 match line {
                     Ok(line_content) => {
-                        let _ = output_sender.send(line_content);
+                        let _ = output_sender.send(strip_ansi(&line_content));
                     }
                     Err(_) => break,
                 }
@@ -355,22 +2122,31 @@ impl HomebrewScanner {
         }
 
         // Wait for the process to complete
-        let exit_status = child
-            .wait()
-            .map_err(|e| format!("Failed to wait for brew process: {}", e))?;
+        let mut child = options
+            .cancel
+            .detach()
+            .ok_or("brew uninstall was detached while running")?;
+        let exit_status = child.wait();
+        command_log::record(&args, exit_status.as_ref().copied(), start.elapsed());
+        let exit_status =
+            exit_status.map_err(|e| format!("Failed to wait for brew process: {}", e))?;
 
+        let stderr = stderr.join().unwrap_or_default();
         if !exit_status.success() {
-            // Read stderr if the command failed
-            if let Some(stderr) = child.stderr.take() {
-                let reader = BufReader::new(stderr);
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line_content) => {
-                            let _ = output_sender.send(line_content);
-                        }
-                        Err(_) => break, // Stop reading on any IO error
-                    }
-                }
+            let mut needs_password = false;
+
+            // Show stderr if the command failed
+            for line in stderr {
+                let line = strip_ansi(&line);
+                needs_password |= Self::is_sudo_password_prompt(&line);
+                let _ = output_sender.send(line);
+            }
+
+            if needs_password {
+                return Err(format!(
+                    "'{}' needs administrator privileges. Run `brew uninstall {} {}` in a terminal, then refresh",
+                    package.name, package_arg, package.name
+                ));
             }
             return Err(format!(
                 "brew uninstall failed with exit code: {:?}",
@@ -384,3 +2160,290 @@ impl HomebrewScanner {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Canned `brew` answers over a throwaway prefix.
+    struct MockBrew {
+        prefix: PathBuf,
+        formulas: Vec<String>,
+        casks: Vec<String>,
+        /// Formulae `brew outdated` reports.
+        outdated: Vec<String>,
+        /// Shell script standing in for `brew uninstall`.
+        uninstall_script: &'static str,
+    }
+
+    impl MockBrew {
+        fn new(prefix: &Path, formulas: &[&str], casks: &[&str]) -> Self {
+            Self {
+                prefix: prefix.to_path_buf(),
+                formulas: formulas.iter().map(|name| name.to_string()).collect(),
+                casks: casks.iter().map(|name| name.to_string()).collect(),
+                outdated: Vec::new(),
+                uninstall_script: "echo Uninstalling",
+            }
+        }
+    }
+
+    impl BrewBackend for MockBrew {
+        fn prefix(&self) -> Result<PathBuf, ScanError> {
+            Ok(self.prefix.clone())
+        }
+
+        fn list_formulas(&self) -> Result<Vec<String>, ScanError> {
+            Ok(self.formulas.clone())
+        }
+
+        fn list_casks(&self) -> Result<Vec<String>, ScanError> {
+            Ok(self.casks.clone())
+        }
+
+        fn info(&self) -> Result<PackageInfoReport, String> {
+            Err("brew info unavailable".to_string())
+        }
+
+        fn info_for(&self, _package: &Package) -> Result<Option<PackageInfo>, String> {
+            Err("brew info unavailable".to_string())
+        }
+
+        fn dependencies(&self) -> Result<HashMap<String, Vec<String>>, String> {
+            Ok(HashMap::new())
+        }
+
+        fn outdated(&self) -> Result<Vec<(String, PackageType)>, String> {
+            Ok(self
+                .outdated
+                .iter()
+                .map(|name| (name.clone(), PackageType::Formula))
+                .collect())
+        }
+
+        fn uninstall(&self, _package: &Package, _args: &[&str]) -> io::Result<Child> {
+            Command::new("sh")
+                .args(["-c", self.uninstall_script])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+        }
+    }
+
+    /// A Homebrew prefix with `wget` 1.24.5 in the Cellar and `firefox` in
+    /// the Caskroom.
+    fn prefix(test: &str) -> PathBuf {
+        let prefix =
+            std::env::temp_dir().join(format!("brewsweep-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&prefix);
+        let keg = prefix.join("Cellar/wget/1.24.5");
+        fs::create_dir_all(keg.join("bin")).unwrap();
+        fs::write(keg.join("bin/wget"), "#!/bin/sh\n").unwrap();
+        fs::write(keg.join("INSTALL_RECEIPT.json"), r#"{"time": 1700000000}"#).unwrap();
+        fs::create_dir_all(prefix.join("Caskroom/firefox/128.0")).unwrap();
+        prefix
+    }
+
+    fn scan(backend: MockBrew, options: ScanOptions) -> Result<Vec<Package>, ScanError> {
+        HomebrewScanner::with_backend(backend)
+            .with_options(options)
+            .scan_blocking()
+    }
+
+    fn uninstall(
+        script: &'static str,
+        options: &UninstallOptions,
+    ) -> (Result<(), String>, Vec<String>) {
+        let backend = MockBrew {
+            uninstall_script: script,
+            ..MockBrew::new(Path::new("/nonexistent"), &[], &[])
+        };
+        let (sender, receiver) = mpsc::channel();
+        let result = HomebrewScanner::with_backend(backend).delete_package_with_output(
+            &wget(),
+            options,
+            sender,
+        );
+        (result, receiver.try_iter().collect())
+    }
+
+    /// `wget` as a scan of the default Homebrew prefix would list it.
+    fn wget() -> Package {
+        Package {
+            name: "wget".to_string(),
+            package_type: PackageType::Formula,
+            version: None,
+            last_accessed: None,
+            time_source: TimeSource::default(),
+            last_accessed_path: None,
+            installed_at: None,
+            paths: vec!["/opt/homebrew/Cellar/wget/1.24.5".to_string()],
+            size_bytes: None,
+            metadata: None,
+            orphans: None,
+            dependents: Vec::new(),
+            outdated: false,
+        }
+    }
+
+    #[test]
+    fn refresh_package_reads_the_backend_prefix() {
+        let prefix = prefix("refresh");
+        let scanner = HomebrewScanner::with_backend(MockBrew::new(&prefix, &["wget"], &[]));
+        let mut package = wget();
+        let result = scanner.refresh_package(&mut package);
+        fs::remove_dir_all(&prefix).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(package.version.as_deref(), Some("1.24.5"));
+        assert_eq!(
+            package.paths,
+            [prefix.join("Cellar/wget/1.24.5").display().to_string()]
+        );
+    }
+
+    #[test]
+    fn scan_reads_packages_listed_by_backend() {
+        let prefix = prefix("scan");
+        let packages = scan(
+            MockBrew::new(&prefix, &["wget"], &["firefox"]),
+            ScanOptions::default(),
+        )
+        .unwrap();
+        fs::remove_dir_all(&prefix).unwrap();
+
+        let wget = packages.iter().find(|p| p.name == "wget").unwrap();
+        assert_eq!(wget.package_type, PackageType::Formula);
+        assert_eq!(wget.version.as_deref(), Some("1.24.5"));
+        assert!(wget.size_bytes.is_some_and(|size| size > 0));
+        assert_eq!(
+            wget.installed_at,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+
+        let firefox = packages.iter().find(|p| p.name == "firefox").unwrap();
+        assert_eq!(firefox.package_type, PackageType::Cask);
+        assert_eq!(firefox.version.as_deref(), Some("128.0"));
+        assert_eq!(packages.len(), 2);
+    }
+
+    #[test]
+    fn scan_skips_kinds_outside_scope() {
+        let prefix = prefix("scope");
+        let options = ScanOptions {
+            scope: ScanScope::Casks,
+            fast: true,
+            ..ScanOptions::default()
+        };
+        let packages = scan(MockBrew::new(&prefix, &["wget"], &["firefox"]), options).unwrap();
+        fs::remove_dir_all(&prefix).unwrap();
+
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["firefox"]);
+    }
+
+    #[test]
+    fn scan_flags_outdated_packages() {
+        let prefix = prefix("outdated");
+        let backend = MockBrew {
+            outdated: vec!["wget".to_string()],
+            ..MockBrew::new(&prefix, &["wget"], &["firefox"])
+        };
+        let options = ScanOptions {
+            fast: true,
+            ..ScanOptions::default()
+        };
+        let packages = scan(backend, options).unwrap();
+        fs::remove_dir_all(&prefix).unwrap();
+
+        let outdated: Vec<&str> = packages
+            .iter()
+            .filter(|p| p.outdated)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(outdated, ["wget"]);
+    }
+
+    #[test]
+    fn find_installed_checks_formulae_then_casks() {
+        let scanner = HomebrewScanner::with_backend(MockBrew::new(
+            Path::new("/nonexistent"),
+            &["wget"],
+            &["firefox"],
+        ));
+        assert_eq!(
+            scanner.find_installed("wget").unwrap(),
+            Some(PackageType::Formula)
+        );
+        assert_eq!(
+            scanner.find_installed("firefox").unwrap(),
+            Some(PackageType::Cask)
+        );
+        assert_eq!(scanner.find_installed("curl").unwrap(), None);
+    }
+
+    #[test]
+    fn uninstall_streams_output() {
+        let (result, lines) = uninstall("echo 'Uninstalling wget'", &UninstallOptions::default());
+        assert_eq!(result, Ok(()));
+        assert_eq!(lines[0], "$ brew uninstall --formula wget");
+        assert!(lines.contains(&"Uninstalling wget".to_string()));
+        assert!(lines
+            .last()
+            .unwrap()
+            .contains("Uninstall completed successfully"));
+    }
+
+    #[test]
+    fn uninstall_failure_reports_stderr() {
+        let (result, lines) = uninstall(
+            "echo 'Error: wget is required by curl' >&2; exit 1",
+            &UninstallOptions::default(),
+        );
+        assert!(result.unwrap_err().contains("exit code: Some(1)"));
+        assert!(lines.contains(&"Error: wget is required by curl".to_string()));
+    }
+
+    #[test]
+    fn uninstall_survives_stderr_larger_than_the_pipe() {
+        let (result, lines) = uninstall(
+            "head -c 200000 /dev/zero | tr '\\0' x >&2; echo 'Uninstalling wget'",
+            &UninstallOptions::default(),
+        );
+        assert_eq!(result, Ok(()));
+        assert!(lines.contains(&"Uninstalling wget".to_string()));
+    }
+
+    #[test]
+    fn dry_run_never_runs_uninstall() {
+        let options = UninstallOptions {
+            dry_run: true,
+            ..UninstallOptions::default()
+        };
+        let (result, lines) = uninstall("echo 'Uninstalling wget'", &options);
+        assert_eq!(result, Ok(()));
+        assert!(!lines.contains(&"Uninstalling wget".to_string()));
+        assert!(lines.contains(&"Would remove: /opt/homebrew/Cellar/wget/1.24.5".to_string()));
+    }
+
+    #[test]
+    fn uninstall_output_has_no_color_codes() {
+        let (_, lines) = uninstall(
+            "printf '\\033[34m==>\\033[0m \\033[1mUninstalling wget\\033[0m\\n'",
+            &UninstallOptions::default(),
+        );
+        assert!(lines.contains(&"==> Uninstalling wget".to_string()));
+    }
+
+    #[test]
+    fn strip_ansi_drops_escape_sequences() {
+        assert_eq!(strip_ansi("\x1b[1;32mok\x1b[0m done"), "ok done");
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://brew.sh\x1b\\brew.sh\x1b]8;;\x07!"),
+            "brew.sh!"
+        );
+        assert_eq!(strip_ansi("\x1b(Bplain\x1b=!"), "plain!");
+        assert_eq!(strip_ansi("no escapes"), "no escapes");
+    }
+}